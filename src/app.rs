@@ -1,44 +1,168 @@
 use std::{
     error,
-    ops::{Index, IndexMut},
+    ops::{Index, IndexMut, Range},
+    str::FromStr,
+    path::{Path, PathBuf},
 };
-use crate::db::{read_user_data, User};
-//
+use chrono::{DateTime, NaiveDate, SubsecRound, Utc};
+use ratatui::layout::Rect;
+use rusqlite::OptionalExtension;
+
+use crate::command::{self, Command};
+use crate::db::{self, Board};
+use crate::editor::LineEditor;
+use crate::fuzzy::fuzzy_match;
+use crate::history::History;
+use crate::keymap::{Keymap, KeymapMode};
+use crate::search::Search;
+use crate::theme::Theme;
+
 pub type AppResult<T> = std::result::Result<T, Box<dyn error::Error>>;
 
+/// Number of board changes that can be undone.
+const HISTORY_LIMIT: usize = 100;
+
+/// Narrowest a list is drawn; lists that do not fit scroll sideways.
+pub const LIST_MIN_WIDTH: u16 = 24;
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum AppMode {
     Main,
     CardEdit,
     ListEdit,
+    BoardPicker,
+    BoardEdit,
+    CardDetail,
+    ChecklistEdit,
+    DescriptionEdit,
+    Search,
+    Palette,
+    Command,
 }
 
-#[derive(Debug)]
+impl AppMode {
+    /// Whether a text field is being edited. The board is only saved once
+    /// editing finishes.
+    pub fn is_editing(self) -> bool {
+        matches!(
+            self,
+            AppMode::CardEdit
+                | AppMode::ListEdit
+                | AppMode::BoardEdit
+                | AppMode::ChecklistEdit
+                | AppMode::DescriptionEdit
+                | AppMode::Search
+                | AppMode::Palette
+                | AppMode::Command
+        )
+    }
+}
+
+/// What `:sort` orders the cards of a list by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Title,
+    Created,
+    Updated,
+    /// Cards without a due date go last.
+    Due,
+}
+
+impl SortKey {
+    pub const NAMES: [&'static str; 4] = ["title", "created", "updated", "due"];
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "title" => Ok(SortKey::Title),
+            "created" => Ok(SortKey::Created),
+            "updated" => Ok(SortKey::Updated),
+            "due" => Ok(SortKey::Due),
+            _ => Err(format!("unknown sort key: {s}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChecklistItem {
+    pub text: String,
+    pub done: bool,
+}
+
+impl ChecklistItem {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            done: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Card {
+    /// Database id, `None` until the card has been saved once.
+    pub id: Option<i64>,
+    pub title: String,
+    pub description: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub due: Option<NaiveDate>,
+    pub labels: Vec<String>,
+    pub assignee: Option<String>,
+    pub checklist: Vec<ChecklistItem>,
+}
+
+impl Card {
+    pub fn new(title: impl Into<String>) -> Self {
+        // Timestamps are stored with second precision, keep them that way in
+        // memory too so a saved card compares equal to the loaded one.
+        let now = Utc::now().trunc_subsecs(0);
+        Self {
+            id: None,
+            title: title.into(),
+            description: String::new(),
+            created_at: now,
+            updated_at: now,
+            due: None,
+            labels: Vec::new(),
+            assignee: None,
+            checklist: Vec::new(),
+        }
+    }
+    /// Marks the card as modified now.
+    pub fn touch(&mut self) {
+        self.updated_at = Utc::now().trunc_subsecs(0);
+    }
+    /// Number of done and total checklist items, `None` without a checklist.
+    pub fn checklist_progress(&self) -> Option<(usize, usize)> {
+        if self.checklist.is_empty() {
+            return None;
+        }
+        let done = self.checklist.iter().filter(|item| item.done).count();
+        Some((done, self.checklist.len()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct CardList {
     name: String,
-    cards: Vec<String>,
-    todo: Vec<String>,
+    cards: Vec<Card>,
 }
 
-// fn getting_data() -> String {
-//     let a: &str = "";
-//     match read_user_data("Jon") {
-//         Ok(user_data) => println!("{:?}", user_data),
-//         Err(_e) => {
-//
-//         }
-//     }
-//
-//     println!("{:?}", user_data);
-//     return user_data.name.to_string();
-// }
-
 impl CardList {
     pub fn new() -> Self {
         Self {
             name: String::from("New List"),
-            cards: vec![String::from("New Card")],
-            todo: vec![String::from("list")],
+            cards: vec![Card::new("New Card")],
+        }
+    }
+    pub fn with_cards(name: String, cards: Vec<Card>) -> Self {
+        Self {
+            name,
+            cards,
         }
     }
     pub fn name(&self) -> &str {
@@ -50,21 +174,18 @@ impl CardList {
     pub fn len(&self) -> usize {
         self.cards.len()
     }
-    pub fn remove(&mut self, index: usize) -> String {
+    pub fn remove(&mut self, index: usize) -> Card {
         self.cards.remove(index)
     }
-    pub fn insert(&mut self, index: usize, element: String) {
+    pub fn insert(&mut self, index: usize, element: Card) {
         self.cards.insert(index, element)
     }
     pub fn swap(&mut self, a: usize, b: usize) {
         self.cards.swap(a, b)
     }
-    pub fn cards(&self) -> &[String] {
+    pub fn cards(&self) -> &[Card] {
         &self.cards
     }
-    pub fn todo(&self) -> &[String] {
-        &self.todo
-    }
 }
 
 impl Default for CardList {
@@ -73,7 +194,7 @@ impl Default for CardList {
     }
 }
 impl Index<usize> for CardList {
-    type Output = String;
+    type Output = Card;
 
     fn index(&self, index: usize) -> &Self::Output {
         &self.cards[index]
@@ -86,73 +207,282 @@ impl IndexMut<usize> for CardList {
     }
 }
 
+/// The board as it was before a change, for undo/redo.
+#[derive(Debug)]
+struct Snapshot {
+    lists: Vec<CardList>,
+    row: usize,
+    col: usize,
+}
+
+/// A card offered by the go to card palette.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaletteItem {
+    pub col: usize,
+    pub row: usize,
+    /// Indices of the characters of the title matching the query.
+    pub positions: Vec<usize>,
+}
+
+/// The query typed in the palette and the cards it matches, best first.
+#[derive(Debug, Default)]
+struct Palette {
+    query: String,
+    items: Vec<PaletteItem>,
+    row: usize,
+}
+
 #[derive(Debug)]
 pub struct App {
     running: bool,
+    board: Board,
+    db_path: PathBuf,
     lists: Vec<CardList>,
     row: usize,
     col: usize,
     mode: AppMode,
-    prev_val: String,
+    editor: LineEditor,
+    dirty: bool,
+    boards: Vec<Board>,
+    board_row: usize,
+    board_offset: usize,
+    detail_scroll: u16,
+    check_row: usize,
+    history: History<Snapshot>,
+    width: u16,
+    height: u16,
+    list_offset: usize,
+    card_offsets: Vec<usize>,
+    dragging: bool,
+    keymap: Keymap,
+    help: bool,
+    help_scroll: u16,
+    theme: Theme,
+    search: Search,
+    palette: Palette,
+    message: Option<String>,
+    /// The board the last press of the delete key asked about.
+    remove_pending: Option<i64>,
+    /// The revision of the board when it was last read or saved.
+    revision: i64,
 }
 
 impl Default for App {
     fn default() -> Self {
-        let user_data = match read_user_data("Jon") {
-
-            Ok(user_data) => user_data,
-
-            Err(e) => {
-
-                eprintln!("Error reading user data: {}", e);
-
-                // Try to read the user data again or return a default user
-
-                match read_user_data("Jon") {
-
-                    Ok(user_data) => user_data,
-
-                    Err(e) => {
-
-                        eprintln!("Failed to read user data again: {}", e);
-
-                        User { id: 0, name: String::new() }
-
-                    }
-
-                }
-
-            }
-
-        };
-
         Self {
             running: true,
+            board: Board {
+                id: 0,
+                name: String::from(db::DEFAULT_BOARD),
+            },
+            db_path: PathBuf::new(),
             row: 0,
             col: 0,
             mode: AppMode::Main,
-            prev_val: String::new(),
-            lists: vec![
-                CardList {
-                    name: user_data.name.to_string(),
-                    cards: vec![
-                        "Card 1 in List 1".to_string(),  // -
-                        "Card 2 in List 1".to_string(),
-                    ],
-                    todo: vec![
-                        "todo 1 in List 1".to_string(),  // -
-                        "todo 2 in List 1".to_string(),
-                    ],
-                },
-            ],
+            editor: LineEditor::default(),
+            dirty: false,
+            boards: Vec::new(),
+            board_row: 0,
+            board_offset: 0,
+            detail_scroll: 0,
+            check_row: 0,
+            history: History::new(HISTORY_LIMIT),
+            width: 80,
+            height: 24,
+            list_offset: 0,
+            card_offsets: Vec::new(),
+            dragging: false,
+            keymap: Keymap::default(),
+            help: false,
+            help_scroll: 0,
+            theme: Theme::default(),
+            search: Search::default(),
+            palette: Palette::default(),
+            message: None,
+            remove_pending: None,
+            revision: 0,
+            lists: starting_lists(),
         }
     }
 }
 
+/// The lists a board gets when the app creates it.
+pub fn starting_lists() -> Vec<CardList> {
+    vec![CardList::with_cards(
+        String::from("List 1"),
+        vec![
+            Card::new("Card 1 in List 1"), // -
+            Card::new("Card 2 in List 1"),
+        ],
+    )]
+}
+
+/// The board opened when none is named, see [`db::last_opened_board`]. A
+/// database without boards gets the default board, with the starting lists.
+pub fn startup_board() -> AppResult<Board> {
+    if let Some(board) = db::last_opened_board()? {
+        return Ok(board);
+    }
+    let board = db::insert_board(db::DEFAULT_BOARD)?;
+    db::write_lists(board.id, &mut starting_lists())?;
+    Ok(board)
+}
+
 impl App {
     pub fn new() -> Self {
         Self::default()
     }
+    /// Loads the board named `name` from the database, creating it with the
+    /// default lists if it does not exist yet.
+    pub fn load(name: &str) -> AppResult<Self> {
+        let mut app = Self {
+            db_path: db::db_path().to_path_buf(),
+            ..Self::default()
+        };
+        match db::find_board(name)? {
+            Some(board) => app.open_board(board)?,
+            None => {
+                app.board = db::insert_board(name)?;
+                db::mark_opened(app.board.id)?;
+                app.dirty = true;
+                app.save()?;
+            }
+        }
+        Ok(app)
+    }
+    /// Loads the board opened last, see [`startup_board`].
+    pub fn load_last() -> AppResult<Self> {
+        let mut app = Self {
+            db_path: db::db_path().to_path_buf(),
+            ..Self::default()
+        };
+        app.open_board(startup_board()?)?;
+        Ok(app)
+    }
+    /// Replaces the board in memory with `board` as stored in the database.
+    fn open_board(&mut self, board: Board) -> AppResult<()> {
+        db::mark_opened(board.id)?;
+        self.revision = db::read_revision(board.id)?;
+        self.lists = db::read_lists(board.id)?;
+        self.board = board;
+        self.row = 0;
+        self.col = 0;
+        self.list_offset = 0;
+        self.card_offsets.clear();
+        self.history.clear();
+        self.dirty = self.lists.is_empty();
+        if self.dirty {
+            self.lists.push(CardList::new());
+        }
+        self.save()
+    }
+    /// Writes the board back to the database if it changed since the last save.
+    ///
+    /// If something else, such as a `kanban-tui add` from a script, changed
+    /// the board in the meantime, the board is read again instead and the
+    /// change that could not be saved is dropped, so it never overwrites
+    /// what the script wrote.
+    pub fn save(&mut self) -> AppResult<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        match db::write_lists_at(self.board.id, self.revision, &mut self.lists)? {
+            Some(revision) => {
+                self.revision = revision;
+                self.dirty = false;
+            }
+            None => {
+                self.reload()?;
+                self.message = Some(String::from(
+                    "the board changed elsewhere and was reloaded, your last change was not saved",
+                ));
+            }
+        }
+        Ok(())
+    }
+    /// Reads the board again after it changed elsewhere.
+    ///
+    /// The undo history only holds versions of the board from before that
+    /// change, so it is dropped. The selected card stays selected if it is
+    /// still on the board; if no card is left to show, the card detail
+    /// closes.
+    fn reload(&mut self) -> AppResult<()> {
+        let selected = self.card().and_then(|card| card.id);
+        self.revision = db::read_revision(self.board.id)?;
+        self.lists = db::read_lists(self.board.id)?;
+        self.history.clear();
+        self.card_offsets.clear();
+        self.dragging = false;
+        self.dirty = self.lists.is_empty();
+        if self.dirty {
+            self.lists.push(CardList::new());
+        }
+        let position = selected.and_then(|id| {
+            self.lists.iter().enumerate().find_map(|(col, list)| {
+                let row = list.cards().iter().position(|card| card.id == Some(id))?;
+                Some((col, row))
+            })
+        });
+        if let Some((col, row)) = position {
+            self.col = col;
+            self.row = row;
+        }
+        self.update_selection();
+        if position.is_none() && self.mode == AppMode::CardDetail {
+            self.mode = AppMode::Main;
+        }
+        self.check_row = self.check_row.min(self.checklist_len().saturating_sub(1));
+        Ok(())
+    }
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+    pub fn db_path(&self) -> &Path {
+        &self.db_path
+    }
+    /// Sets the database path shown in the header.
+    pub fn set_db_path(&mut self, path: PathBuf) {
+        self.db_path = path;
+    }
+    pub fn boards(&self) -> &[Board] {
+        &self.boards
+    }
+    pub fn board_row(&self) -> usize {
+        self.board_row
+    }
+    /// Position of the first board on screen in the board picker.
+    pub fn board_offset(&self) -> usize {
+        self.board_offset
+    }
+    /// The text being edited in the edit modes.
+    pub fn editor(&self) -> &LineEditor {
+        &self.editor
+    }
+    pub fn editor_mut(&mut self) -> &mut LineEditor {
+        &mut self.editor
+    }
+    /// The key bindings, see [`Keymap`].
+    pub fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+    pub fn set_keymap(&mut self, keymap: Keymap) {
+        self.keymap = keymap;
+    }
+    /// The styles the interface is drawn with.
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+    /// The keymap for the current mode, or the help overlay if it is open.
+    pub fn keymap_mode(&self) -> KeymapMode {
+        if self.help {
+            KeymapMode::Help
+        } else {
+            self.mode.into()
+        }
+    }
     pub fn mode(&self) -> AppMode {
         self.mode
     }
@@ -162,6 +492,16 @@ impl App {
     pub fn list(&self) -> &CardList {
         &self.lists[self.col]
     }
+    /// The selected card, if the selected list has any.
+    pub fn card(&self) -> Option<&Card> {
+        self.list().cards().get(self.row)
+    }
+    pub fn detail_scroll(&self) -> u16 {
+        self.detail_scroll
+    }
+    pub fn check_row(&self) -> usize {
+        self.check_row
+    }
     pub fn row(&self) -> usize {
         self.row
     }
@@ -178,7 +518,102 @@ impl App {
         self.running
     }
 
-    pub fn tick(&self) {}
+    /// Reloads the board if something else changed it and there is nothing
+    /// unsaved or being edited here. Returns whether the board was reloaded.
+    pub fn tick(&mut self) -> AppResult<bool> {
+        if self.dirty || self.mode.is_editing() {
+            return Ok(false);
+        }
+        let Some(revision) = db::read_revision(self.board.id).optional()? else {
+            return Ok(false);
+        };
+        if revision == self.revision {
+            return Ok(false);
+        }
+        self.reload()?;
+        Ok(true)
+    }
+
+    /// Updates the terminal size the board is laid out for.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.width = width;
+        self.height = height;
+        self.scroll_to_selection();
+        self.scroll_to_board();
+    }
+    /// The whole terminal, as last reported to [`App::resize`].
+    pub fn area(&self) -> Rect {
+        Rect::new(0, 0, self.width, self.height)
+    }
+    /// How many lists fit side by side. Lists sit inside a one cell margin
+    /// with one cell between them.
+    pub fn visible_cols(&self) -> usize {
+        usize::from(self.width.saturating_sub(1) / (LIST_MIN_WIDTH + 1)).max(1)
+    }
+    /// Indices of the lists currently on screen.
+    pub fn visible_lists(&self) -> Range<usize> {
+        let end = (self.list_offset + self.visible_cols()).min(self.cols());
+        self.list_offset..end
+    }
+    /// How many cards fit in a list: the terminal height minus the header,
+    /// footer, board margin and list borders.
+    pub fn visible_rows(&self) -> usize {
+        usize::from(self.height.saturating_sub(10)).max(1)
+    }
+    /// How many boards the board picker shows at once, leaving room for its
+    /// borders above the footer.
+    pub fn visible_boards(&self) -> usize {
+        usize::from(self.height.saturating_sub(8)).max(1)
+    }
+    /// Position of the first card on screen among the
+    /// [shown cards](App::shown_cards) of the list at `col`.
+    pub fn card_offset(&self, col: usize) -> usize {
+        let max = self.shown_cards(col).len().saturating_sub(self.visible_rows());
+        self.card_offsets.get(col).copied().unwrap_or(0).min(max)
+    }
+    /// Indices of the cards drawn in the list at `col`: those matching the
+    /// search, and the selected card so that it never disappears.
+    pub fn shown_cards(&self, col: usize) -> Vec<usize> {
+        self.lists[col]
+            .cards()
+            .iter()
+            .enumerate()
+            .filter(|(row, card)| (col == self.col && *row == self.row) || self.search.matches(card))
+            .map(|(row, _)| row)
+            .collect()
+    }
+    /// Scrolls the lists, and the cards of the selected list, just enough for
+    /// the selected card to be on screen. Other lists keep their scroll
+    /// position.
+    fn scroll_to_selection(&mut self) {
+        let visible = self.visible_cols();
+        self.list_offset = self
+            .list_offset
+            .min(self.col)
+            .max((self.col + 1).saturating_sub(visible))
+            .min(self.cols().saturating_sub(visible));
+
+        self.card_offsets.resize(self.cols(), 0);
+        let visible = self.visible_rows();
+        let shown = self.shown_cards(self.col);
+        let row = shown.iter().position(|&row| row == self.row).unwrap_or(0);
+        let offset = &mut self.card_offsets[self.col];
+        *offset = (*offset)
+            .min(row)
+            .max((row + 1).saturating_sub(visible))
+            .min(shown.len().saturating_sub(visible));
+    }
+
+    /// Scrolls the board picker just enough for the selected board to be on
+    /// screen.
+    fn scroll_to_board(&mut self) {
+        let visible = self.visible_boards();
+        self.board_offset = self
+            .board_offset
+            .min(self.board_row)
+            .max((self.board_row + 1).saturating_sub(visible))
+            .min(self.boards.len().saturating_sub(visible));
+    }
 
     pub fn quit(&mut self) {
         self.running = false;
@@ -187,53 +622,149 @@ impl App {
     fn update_selection(&mut self) {
         self.col = self.col.min(self.cols().saturating_sub(1));
         self.row = self.row.min(self.rows().saturating_sub(1));
+        self.scroll_to_selection();
+    }
+
+    // Undo/Redo
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            lists: self.lists.clone(),
+            row: self.row,
+            col: self.col,
+        }
+    }
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.lists = snapshot.lists;
+        self.row = snapshot.row;
+        self.col = snapshot.col;
+        self.card_offsets.clear();
+        self.update_selection();
+        self.dirty = true;
+    }
+    /// Records the board before a change so the change can be undone.
+    fn checkpoint(&mut self) {
+        let snapshot = self.snapshot();
+        self.history.record(snapshot);
+    }
+    /// Drops the last checkpoint if the board still matches it, as after a
+    /// cancelled edit.
+    fn discard_checkpoint(&mut self) {
+        if self.history.last().is_some_and(|last| last.lists == self.lists) {
+            self.history.discard();
+        }
+    }
+    pub fn undo(&mut self) {
+        if !self.history.can_undo() {
+            return;
+        }
+        let current = self.snapshot();
+        if let Some(snapshot) = self.history.undo(current) {
+            self.restore(snapshot);
+        }
+    }
+    pub fn redo(&mut self) {
+        if !self.history.can_redo() {
+            return;
+        }
+        let current = self.snapshot();
+        if let Some(snapshot) = self.history.redo(current) {
+            self.restore(snapshot);
+        }
     }
 
     // Selection Motions
     pub fn motion_left(&mut self) {
         self.col = self.col.saturating_sub(1);
-        self.update_selection();
+        self.snap_to_match();
     }
     pub fn motion_down(&mut self) {
-        self.row += 1;
+        let shown = self.shown_cards(self.col);
+        if let Some(&row) = shown.iter().find(|&&row| row > self.row) {
+            self.row = row;
+        }
         self.update_selection();
     }
     pub fn motion_up(&mut self) {
-        self.row = self.row.saturating_sub(1);
+        let shown = self.shown_cards(self.col);
+        if let Some(&row) = shown.iter().rev().find(|&&row| row < self.row) {
+            self.row = row;
+        }
         self.update_selection();
     }
     pub fn motion_right(&mut self) {
         self.col += 1;
+        self.snap_to_match();
+    }
+    /// Moves the selection within its list to the nearest card matching the
+    /// search, if it is on one that does not.
+    fn snap_to_match(&mut self) {
+        self.update_selection();
+        let cards = self.lists[self.col].cards();
+        if cards.get(self.row).is_none_or(|card| self.search.matches(card)) {
+            return;
+        }
+        let matching: Vec<usize> = (0..cards.len())
+            .filter(|&row| self.search.matches(&cards[row]))
+            .collect();
+        let after = matching.iter().find(|&&row| row > self.row);
+        if let Some(&row) = after.or(matching.last()) {
+            self.row = row;
+        }
         self.update_selection();
     }
 
+    /// Selects the list at `col`, and the card at `row` if given.
+    pub fn select(&mut self, col: usize, row: Option<usize>) {
+        self.col = col;
+        match row {
+            Some(row) => {
+                self.row = row;
+                self.update_selection();
+            }
+            None => self.snap_to_match(),
+        }
+    }
+
     // Card Movements
     pub fn move_left(&mut self) {
         if self.col == 0 || self.row >= self.rows() {
             return;
         }
+        self.checkpoint();
         let t_col = self.col - 1;
         let t_row = self.row.min(self.lists[t_col].len());
         let card = self.lists[self.col].remove(self.row);
         self.lists[t_col].insert(t_row, card);
         self.row = t_row;
         self.col = t_col;
+        self.update_selection();
+        self.dirty = true;
     }
+    /// Swaps the selected card with the next one shown, skipping cards
+    /// hidden by the search.
     pub fn move_down(&mut self) {
-        let t_row = self.row + 1;
-        if t_row >= self.rows() {
+        let shown = self.shown_cards(self.col);
+        let Some(&t_row) = shown.iter().find(|&&row| row > self.row) else {
             return;
-        }
+        };
+        self.checkpoint();
         self.lists[self.col].swap(self.row, t_row);
         self.row = t_row;
+        self.update_selection();
+        self.dirty = true;
     }
+    /// Swaps the selected card with the previous one shown, skipping cards
+    /// hidden by the search.
     pub fn move_up(&mut self) {
-        let t_row = self.row.saturating_sub(1);
-        if t_row >= self.rows() {
+        let shown = self.shown_cards(self.col);
+        let Some(&t_row) = shown.iter().rev().find(|&&row| row < self.row) else {
             return;
-        }
+        };
+        self.checkpoint();
         self.lists[self.col].swap(self.row, t_row);
         self.row = t_row;
+        self.update_selection();
+        self.dirty = true;
     }
     pub fn move_right(&mut self) {
         if self.row >= self.rows() {
@@ -243,11 +774,55 @@ impl App {
         if t_col >= self.cols() {
             return;
         }
+        self.checkpoint();
         let t_row = self.row.min(self.lists[t_col].len());
         let card = self.lists[self.col].remove(self.row);
         self.lists[t_col].insert(t_row, card);
         self.row = t_row;
         self.col = t_col;
+        self.update_selection();
+        self.dirty = true;
+    }
+
+    // Dragging
+    /// Starts dragging the selected card, if there is one. The whole drag is
+    /// undone in one step.
+    pub fn start_drag(&mut self) {
+        self.dragging = self.row < self.rows();
+        if self.dragging {
+            self.checkpoint();
+        }
+    }
+    pub fn stop_drag(&mut self) {
+        if self.dragging {
+            self.discard_checkpoint();
+        }
+        self.dragging = false;
+    }
+    /// Moves the dragged card to the list at `col`, and to `row` within it if
+    /// given.
+    pub fn drag_to(&mut self, col: usize, row: Option<usize>) {
+        if !self.dragging || self.row >= self.rows() {
+            return;
+        }
+        let col = col.min(self.cols() - 1);
+        if col != self.col {
+            let t_row = self.row.min(self.lists[col].len());
+            let card = self.lists[self.col].remove(self.row);
+            self.lists[col].insert(t_row, card);
+            self.row = t_row;
+            self.col = col;
+            self.dirty = true;
+        }
+        if let Some(row) = row.map(|row| row.min(self.rows() - 1)) {
+            if row != self.row {
+                let card = self.lists[self.col].remove(self.row);
+                self.lists[self.col].insert(row, card);
+                self.row = row;
+                self.dirty = true;
+            }
+        }
+        self.update_selection();
     }
 
     // Edit Card
@@ -255,28 +830,480 @@ impl App {
         if self.row >= self.rows() {
             return;
         }
-        self.mode = AppMode::CardEdit;
-        self.prev_val = self.lists[self.col][self.row].clone();
-    }
-    pub fn type_card(&mut self, c: char) {
-        self.lists[self.col][self.row].push(c);
+        self.checkpoint();
+        self.start_card_edit();
     }
-    pub fn backspace_card(&mut self) {
-        self.lists[self.col][self.row].pop();
+    fn start_card_edit(&mut self) {
+        self.mode = AppMode::CardEdit;
+        self.editor = LineEditor::new(self.lists[self.col][self.row].title.clone());
     }
+    /// Stores the edited card title or list name.
     pub fn done_editing(&mut self) {
+        let text = std::mem::take(&mut self.editor).into_text();
+        match self.mode {
+            AppMode::CardEdit => {
+                let card = &mut self.lists[self.col][self.row];
+                if card.title != text {
+                    card.title = text;
+                    card.touch();
+                }
+            }
+            AppMode::ListEdit => self.lists[self.col].name = text,
+            _ => {}
+        }
+        self.discard_checkpoint();
         self.mode = AppMode::Main;
+        self.dirty = true;
     }
     pub fn cancel_card_edit(&mut self) {
-        self.lists[self.col][self.row] = self.prev_val.clone();
+        self.discard_checkpoint();
         self.mode = AppMode::Main;
     }
 
+    /// Adds pasted lines as cards. While editing a card the first line is
+    /// inserted at the cursor and the others become new cards after it;
+    /// otherwise every line becomes a new card after the selected one.
+    pub fn paste_cards(&mut self, lines: &[&str]) {
+        let Some((first, rest)) = lines.split_first() else {
+            return;
+        };
+        let rest = if self.mode == AppMode::CardEdit {
+            self.editor.insert_str(first);
+            if rest.is_empty() {
+                return;
+            }
+            self.done_editing();
+            rest
+        } else {
+            self.checkpoint();
+            lines
+        };
+        for line in rest {
+            let index = (self.row + 1).min(self.rows());
+            self.lists[self.col].insert(index, Card::new(*line));
+            self.row = index;
+        }
+        self.update_selection();
+        self.dirty = true;
+    }
+
+    // Card Details
+    pub fn open_card_detail(&mut self) {
+        if self.row >= self.rows() {
+            return;
+        }
+        self.detail_scroll = 0;
+        self.check_row = 0;
+        self.mode = AppMode::CardDetail;
+    }
+    pub fn close_card_detail(&mut self) {
+        self.mode = AppMode::Main;
+    }
+    /// Edits the description of the selected card.
+    pub fn edit_description(&mut self) {
+        let Some(card) = self.card() else {
+            return;
+        };
+        self.editor = LineEditor::new(card.description.clone());
+        self.checkpoint();
+        self.mode = AppMode::DescriptionEdit;
+    }
+    pub fn done_description_edit(&mut self) {
+        let text = std::mem::take(&mut self.editor).into_text();
+        self.change_card(|card| card.description = text);
+        self.discard_checkpoint();
+        self.mode = AppMode::CardDetail;
+    }
+    pub fn cancel_description_edit(&mut self) {
+        self.discard_checkpoint();
+        self.mode = AppMode::CardDetail;
+    }
+    pub fn detail_scroll_down(&mut self) {
+        self.detail_scroll = self.detail_scroll.saturating_add(1);
+    }
+    pub fn detail_scroll_up(&mut self) {
+        self.detail_scroll = self.detail_scroll.saturating_sub(1);
+    }
+
+    // Card Fields
+    /// Sets the due date of the selected card, or clears it with `None`.
+    pub fn set_due(&mut self, due: Option<NaiveDate>) {
+        self.checkpoint();
+        self.change_card(|card| card.due = due);
+        self.discard_checkpoint();
+    }
+    /// Adds `label` to the selected card, or removes it if the card has it.
+    pub fn toggle_label(&mut self, label: String) {
+        self.checkpoint();
+        self.change_card(|card| match card.labels.iter().position(|l| *l == label) {
+            Some(index) => {
+                card.labels.remove(index);
+            }
+            None => card.labels.push(label),
+        });
+        self.discard_checkpoint();
+    }
+    /// Sets who the selected card is assigned to, or clears it with `None`.
+    pub fn set_assignee(&mut self, assignee: Option<String>) {
+        self.checkpoint();
+        self.change_card(|card| card.assignee = assignee);
+        self.discard_checkpoint();
+    }
+    /// Applies `change` to the selected card, marking it updated if that
+    /// changed anything.
+    fn change_card(&mut self, change: impl FnOnce(&mut Card)) {
+        if self.row >= self.rows() {
+            return;
+        }
+        let card = &mut self.lists[self.col][self.row];
+        let before = card.clone();
+        change(card);
+        if *card != before {
+            card.touch();
+            self.dirty = true;
+        }
+    }
+    /// Every label used on the board, sorted.
+    pub fn labels(&self) -> Vec<String> {
+        let mut labels: Vec<String> = self
+            .lists
+            .iter()
+            .flat_map(|list| list.cards())
+            .flat_map(|card| card.labels.iter().cloned())
+            .collect();
+        labels.sort();
+        labels.dedup();
+        labels
+    }
+    /// Everyone a card on the board is assigned to, sorted.
+    pub fn assignees(&self) -> Vec<String> {
+        let mut assignees: Vec<String> = self
+            .lists
+            .iter()
+            .flat_map(|list| list.cards())
+            .filter_map(|card| card.assignee.clone())
+            .collect();
+        assignees.sort();
+        assignees.dedup();
+        assignees
+    }
+
+    // Help
+    /// Whether the help overlay is shown over the current mode.
+    pub fn help(&self) -> bool {
+        self.help
+    }
+    pub fn help_scroll(&self) -> u16 {
+        self.help_scroll
+    }
+    pub fn open_help(&mut self) {
+        self.help = true;
+        self.help_scroll = 0;
+    }
+    pub fn close_help(&mut self) {
+        self.help = false;
+    }
+    pub fn help_scroll_down(&mut self) {
+        self.help_scroll = self.help_scroll.saturating_add(1);
+    }
+    pub fn help_scroll_up(&mut self) {
+        self.help_scroll = self.help_scroll.saturating_sub(1);
+    }
+
+    // Search
+    /// The search filtering the board, see [`Search`].
+    pub fn search(&self) -> &Search {
+        &self.search
+    }
+    pub fn start_search(&mut self) {
+        self.mode = AppMode::Search;
+        self.editor = LineEditor::new(self.search.query());
+    }
+    /// Applies the query being typed, selecting the next matching card if
+    /// the selected one no longer matches.
+    pub fn update_search(&mut self) {
+        self.search = Search::new(self.editor.text(), self.search.regex());
+        let matches = self.card().is_some_and(|card| self.search.matches(card));
+        if !matches {
+            self.jump_to_match(true);
+        }
+        self.card_offsets.clear();
+        self.update_selection();
+    }
+    pub fn toggle_search_regex(&mut self) {
+        self.search = Search::new(self.search.query(), !self.search.regex());
+        self.update_search();
+    }
+    /// Keeps the search as typed, and back to the board.
+    pub fn done_search(&mut self) {
+        self.editor = LineEditor::default();
+        self.mode = AppMode::Main;
+    }
+    pub fn cancel_search(&mut self) {
+        self.done_search();
+        self.clear_search();
+    }
+    /// Shows every card again.
+    pub fn clear_search(&mut self) {
+        self.search = Search::new("", self.search.regex());
+        self.card_offsets.clear();
+        self.update_selection();
+    }
+    pub fn search_next(&mut self) {
+        self.jump_to_match(false);
+    }
+    pub fn search_prev(&mut self) {
+        let matching = self.matching_cards();
+        let here = (self.col, self.row);
+        let prev = matching.iter().rev().find(|&&at| at < here);
+        if let Some(&(col, row)) = prev.or(matching.last()) {
+            self.select(col, Some(row));
+        }
+    }
+    /// Selects the first matching card after the selected one, in reading
+    /// order and wrapping around, or the selected one itself if `inclusive`.
+    fn jump_to_match(&mut self, inclusive: bool) {
+        let matching = self.matching_cards();
+        let here = (self.col, self.row);
+        let next = matching
+            .iter()
+            .find(|&&at| at > here || (inclusive && at == here));
+        if let Some(&(col, row)) = next.or(matching.first()) {
+            self.select(col, Some(row));
+        }
+    }
+    // Command Line
+    /// The outcome of the last command, shown in the footer until the next
+    /// key press.
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+    pub fn set_message(&mut self, message: String) {
+        self.message = Some(message);
+    }
+    pub fn clear_message(&mut self) {
+        self.message = None;
+    }
+    pub fn start_command(&mut self) -> AppResult<()> {
+        // For completing board names
+        self.reload_boards()?;
+        self.mode = AppMode::Command;
+        self.editor = LineEditor::default();
+        Ok(())
+    }
+    /// Runs the command typed at the prompt. Mistakes and failures are
+    /// reported in the footer rather than ending the app.
+    pub fn done_command(&mut self) {
+        let input = std::mem::take(&mut self.editor).into_text();
+        self.mode = AppMode::Main;
+        let result = input
+            .parse::<Command>()
+            .map_err(Into::into)
+            .and_then(|command| command.run(self));
+        self.message = match result {
+            Ok(message) => message,
+            Err(e) => Some(e.to_string()),
+        };
+    }
+    pub fn cancel_command(&mut self) {
+        self.editor = LineEditor::default();
+        self.mode = AppMode::Main;
+    }
+    /// Completes the word before the cursor, listing the candidates in the
+    /// footer if there are several.
+    pub fn complete_command(&mut self) {
+        let (input, candidates) = command::complete(self.editor.text(), self);
+        self.editor = LineEditor::new(input);
+        self.message = (!candidates.is_empty()).then(|| candidates.join("  "));
+    }
+
+    // Go To Card
+    /// The cards matching the palette query, best first.
+    pub fn palette_items(&self) -> &[PaletteItem] {
+        &self.palette.items
+    }
+    pub fn palette_row(&self) -> usize {
+        self.palette.row
+    }
+    pub fn open_palette(&mut self) {
+        self.mode = AppMode::Palette;
+        self.editor = LineEditor::default();
+        self.rank_palette();
+    }
+    /// Applies the query being typed, if it changed.
+    pub fn update_palette(&mut self) {
+        if self.palette.query != self.editor.text() {
+            self.rank_palette();
+        }
+    }
+    /// Ranks every card on the board against the query.
+    fn rank_palette(&mut self) {
+        let query = self.editor.text();
+        let mut ranked: Vec<(i64, PaletteItem)> = Vec::new();
+        for (col, list) in self.lists.iter().enumerate() {
+            for (row, card) in list.cards().iter().enumerate() {
+                if let Some((score, positions)) = fuzzy_match(query, &card.title) {
+                    let item = PaletteItem {
+                        col,
+                        row,
+                        positions,
+                    };
+                    ranked.push((score, item));
+                }
+            }
+        }
+        // Stable, so equal scores keep the board order
+        ranked.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        self.palette.items = ranked.into_iter().map(|(_, item)| item).collect();
+        self.palette.query = query.to_string();
+        self.palette.row = 0;
+    }
+    pub fn palette_down(&mut self) {
+        if self.palette.row + 1 < self.palette.items.len() {
+            self.palette.row += 1;
+        }
+    }
+    pub fn palette_up(&mut self) {
+        self.palette.row = self.palette.row.saturating_sub(1);
+    }
+    /// Selects the chosen card on the board.
+    pub fn done_palette(&mut self) {
+        let chosen = self.palette.items.get(self.palette.row);
+        if let Some(&PaletteItem { col, row, .. }) = chosen {
+            self.select(col, Some(row));
+        }
+        self.cancel_palette();
+    }
+    pub fn cancel_palette(&mut self) {
+        self.palette = Palette::default();
+        self.editor = LineEditor::default();
+        self.mode = AppMode::Main;
+    }
+
+    /// The `(col, row)` of every card matching an active search.
+    pub fn matching_cards(&self) -> Vec<(usize, usize)> {
+        if !self.search.is_active() {
+            return Vec::new();
+        }
+        self.lists
+            .iter()
+            .enumerate()
+            .flat_map(|(col, list)| {
+                list.cards()
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, card)| self.search.matches(card))
+                    .map(move |(row, _)| (col, row))
+            })
+            .collect()
+    }
+
+    // Checklist
+    /// The number of items on the selected card's checklist, 0 without a
+    /// card, so the item helpers below never index past the board.
+    fn checklist_len(&self) -> usize {
+        self.card().map_or(0, |card| card.checklist.len())
+    }
+    fn checklist(&mut self) -> &mut Vec<ChecklistItem> {
+        &mut self.lists[self.col][self.row].checklist
+    }
+    fn checklist_changed(&mut self) {
+        self.lists[self.col][self.row].touch();
+        self.dirty = true;
+    }
+    pub fn check_motion_down(&mut self) {
+        self.check_row = (self.check_row + 1).min(self.checklist_len().saturating_sub(1));
+    }
+    pub fn check_motion_up(&mut self) {
+        self.check_row = self.check_row.saturating_sub(1);
+    }
+    pub fn toggle_check_item(&mut self) {
+        let row = self.check_row;
+        if row >= self.checklist_len() {
+            return;
+        }
+        self.checkpoint();
+        let item = &mut self.checklist()[row];
+        item.done = !item.done;
+        self.checklist_changed();
+    }
+    pub fn move_check_item_down(&mut self) {
+        let t_row = self.check_row + 1;
+        if t_row >= self.checklist_len() {
+            return;
+        }
+        self.checkpoint();
+        let row = self.check_row;
+        self.checklist().swap(row, t_row);
+        self.check_row = t_row;
+        self.checklist_changed();
+    }
+    pub fn move_check_item_up(&mut self) {
+        if self.check_row == 0 || self.check_row >= self.checklist_len() {
+            return;
+        }
+        self.checkpoint();
+        let row = self.check_row;
+        self.checklist().swap(row, row - 1);
+        self.check_row -= 1;
+        self.checklist_changed();
+    }
+    pub fn append_check_item(&mut self) {
+        if self.card().is_none() {
+            return;
+        }
+        self.checkpoint();
+        let index = (self.check_row + 1).min(self.checklist_len());
+        self.checklist().insert(index, ChecklistItem::new("New Item"));
+        self.check_row = index;
+        self.checklist_changed();
+        self.start_check_edit();
+    }
+    pub fn edit_check_item(&mut self) {
+        if self.check_row >= self.checklist_len() {
+            return;
+        }
+        self.checkpoint();
+        self.start_check_edit();
+    }
+    fn start_check_edit(&mut self) {
+        let row = self.check_row;
+        self.editor = LineEditor::new(self.checklist()[row].text.clone());
+        self.mode = AppMode::ChecklistEdit;
+    }
+    pub fn done_check_edit(&mut self) {
+        let row = self.check_row;
+        let text = std::mem::take(&mut self.editor).into_text();
+        if row < self.checklist_len() && self.checklist()[row].text != text {
+            self.checklist()[row].text = text;
+            self.checklist_changed();
+        }
+        self.discard_checkpoint();
+        self.mode = AppMode::CardDetail;
+    }
+    pub fn cancel_check_edit(&mut self) {
+        self.discard_checkpoint();
+        self.mode = AppMode::CardDetail;
+    }
+    pub fn remove_check_item(&mut self) {
+        let row = self.check_row;
+        if row >= self.checklist_len() {
+            return;
+        }
+        self.checkpoint();
+        self.checklist().remove(row);
+        self.check_row = row.min(self.checklist_len().saturating_sub(1));
+        self.checklist_changed();
+    }
+
     // Add/Remove Card
     fn add_card(&mut self, index: usize) {
-        self.lists[self.col].insert(index, String::from("New Card"));
+        self.checkpoint();
+        self.lists[self.col].insert(index, Card::new("New Card"));
         self.row = index;
-        self.edit_card();
+        self.update_selection();
+        self.dirty = true;
+        self.start_card_edit();
     }
     pub fn append_card(&mut self) {
         let index = (self.row + 1).min(self.rows());
@@ -289,39 +1316,384 @@ impl App {
         if self.lists[self.col].is_empty() {
             return;
         }
+        self.checkpoint();
         self.lists[self.col].remove(self.row);
         self.update_selection();
+        self.dirty = true;
     }
 
     // Edit List
     pub fn edit_list(&mut self) {
-        self.mode = AppMode::ListEdit;
-        self.prev_val = self.list().name.clone();
-    }
-    pub fn type_list(&mut self, c: char) {
-        self.lists[self.col].name.push(c);
+        self.checkpoint();
+        self.start_list_edit();
     }
-    pub fn backspace_list(&mut self) {
-        self.lists[self.col].name.pop();
+    fn start_list_edit(&mut self) {
+        self.mode = AppMode::ListEdit;
+        self.editor = LineEditor::new(self.list().name.clone());
     }
     pub fn cancel_list_edit(&mut self) {
-        self.lists[self.col].name = self.prev_val.clone();
+        self.discard_checkpoint();
         self.mode = AppMode::Main;
     }
     pub fn append_list(&mut self) {
+        self.checkpoint();
         self.col += 1;
         self.lists.insert(self.col, CardList::new());
-        self.edit_list();
+        self.card_offsets.insert(self.col.min(self.card_offsets.len()), 0);
+        self.update_selection();
+        self.dirty = true;
+        self.start_list_edit();
     }
     pub fn prepend_list(&mut self) {
+        self.checkpoint();
         self.lists.insert(self.col, CardList::new());
-        self.edit_list();
+        self.card_offsets.insert(self.col.min(self.card_offsets.len()), 0);
+        self.update_selection();
+        self.dirty = true;
+        self.start_list_edit();
+    }
+    /// Adds a list called `name` after the selected one.
+    pub fn add_list(&mut self, name: String) {
+        self.checkpoint();
+        self.col = (self.col + 1).min(self.cols());
+        self.lists
+            .insert(self.col, CardList::with_cards(name, Vec::new()));
+        self.card_offsets
+            .insert(self.col.min(self.card_offsets.len()), 0);
+        self.update_selection();
+        self.dirty = true;
+    }
+    pub fn rename_list(&mut self, name: String) {
+        self.checkpoint();
+        self.lists[self.col].name = name;
+        self.dirty = true;
+    }
+    /// Moves the selected card to the end of the list at `col`.
+    pub fn move_card_to(&mut self, col: usize) {
+        if self.row >= self.rows() || col == self.col || col >= self.cols() {
+            return;
+        }
+        self.checkpoint();
+        let card = self.lists[self.col].remove(self.row);
+        let end = self.lists[col].len();
+        self.lists[col].insert(end, card);
+        self.col = col;
+        self.row = self.rows() - 1;
+        self.update_selection();
+        self.dirty = true;
+    }
+    /// Replaces every list of the board, as an undoable change.
+    pub fn replace_lists(&mut self, lists: Vec<CardList>) {
+        if lists.is_empty() {
+            return;
+        }
+        self.checkpoint();
+        self.lists = lists;
+        self.row = 0;
+        self.col = 0;
+        self.list_offset = 0;
+        self.card_offsets.clear();
+        self.update_selection();
+        self.dirty = true;
+    }
+    /// Sorts the cards of the selected list, keeping the selected card
+    /// selected.
+    pub fn sort_cards(&mut self, key: SortKey) {
+        self.checkpoint();
+        let selected = self.card().cloned();
+        let cards = &mut self.lists[self.col].cards;
+        match key {
+            SortKey::Title => cards.sort_by_key(|card| card.title.to_lowercase()),
+            SortKey::Created => cards.sort_by_key(|card| card.created_at),
+            SortKey::Updated => cards.sort_by_key(|card| card.updated_at),
+            SortKey::Due => cards.sort_by_key(|card| (card.due.is_none(), card.due)),
+        }
+        if let Some(selected) = selected {
+            self.row = cards.iter().position(|card| *card == selected).unwrap_or(0);
+        }
+        self.discard_checkpoint();
+        self.update_selection();
+        self.dirty = true;
     }
     pub fn remove_list(&mut self) {
         if self.cols() == 1 {
             return;
         }
+        self.checkpoint();
         self.lists.remove(self.col);
+        if self.col < self.card_offsets.len() {
+            self.card_offsets.remove(self.col);
+        }
         self.update_selection();
+        self.dirty = true;
+    }
+
+    // Boards
+    pub fn open_board_picker(&mut self) -> AppResult<()> {
+        self.save()?;
+        self.reload_boards()?;
+        self.board_row = self
+            .boards
+            .iter()
+            .position(|board| board.id == self.board.id)
+            .unwrap_or(0);
+        self.scroll_to_board();
+        self.mode = AppMode::BoardPicker;
+        Ok(())
+    }
+    pub fn close_board_picker(&mut self) {
+        self.mode = AppMode::Main;
+    }
+    fn reload_boards(&mut self) -> AppResult<()> {
+        self.boards = db::read_boards()?;
+        self.board_row = self.board_row.min(self.boards.len().saturating_sub(1));
+        self.scroll_to_board();
+        Ok(())
+    }
+    pub fn board_motion_down(&mut self) {
+        self.board_row = (self.board_row + 1).min(self.boards.len().saturating_sub(1));
+        self.scroll_to_board();
+    }
+    pub fn board_motion_up(&mut self) {
+        self.board_row = self.board_row.saturating_sub(1);
+        self.scroll_to_board();
+    }
+    pub fn switch_board(&mut self) -> AppResult<()> {
+        let Some(board) = self.boards.get(self.board_row).cloned() else {
+            return Ok(());
+        };
+        if board.id != self.board.id {
+            self.save()?;
+            self.open_board(board)?;
+        }
+        self.mode = AppMode::Main;
+        Ok(())
+    }
+    /// Opens the board called `name`, creating it if there is none.
+    pub fn open_board_named(&mut self, name: &str) -> AppResult<()> {
+        if name == self.board.name {
+            return Ok(());
+        }
+        self.save()?;
+        match db::find_board(name)? {
+            Some(board) => self.open_board(board),
+            None => {
+                let board = db::insert_board(name)?;
+                self.open_board(board)
+            }
+        }
+    }
+    /// Returns `base`, suffixed with a number if another board already uses it.
+    fn unique_board_name(&self, base: &str) -> String {
+        let taken = |name: &str| self.boards.iter().any(|board| board.name == name);
+        (1..)
+            .map(|n| match n {
+                1 => base.to_string(),
+                n => format!("{base} {n}"),
+            })
+            .find(|name| !taken(name))
+            .unwrap_or_default()
+    }
+    pub fn append_board(&mut self) -> AppResult<()> {
+        let board = db::insert_board(&self.unique_board_name("New Board"))?;
+        self.reload_boards()?;
+        self.board_row = self
+            .boards
+            .iter()
+            .position(|b| b.id == board.id)
+            .unwrap_or(0);
+        self.scroll_to_board();
+        self.edit_board();
+        Ok(())
+    }
+    pub fn edit_board(&mut self) {
+        if let Some(board) = self.boards.get(self.board_row) {
+            self.editor = LineEditor::new(board.name.clone());
+            self.mode = AppMode::BoardEdit;
+        }
+    }
+    /// Stores the edited board name. Empty names and names used by another
+    /// board are rejected and the previous name is kept.
+    pub fn done_board_edit(&mut self) -> AppResult<()> {
+        let board = &self.boards[self.board_row];
+        let name = self.editor.text().trim().to_string();
+        let taken = self
+            .boards
+            .iter()
+            .any(|other| other.id != board.id && other.name == name);
+        if name.is_empty() || taken {
+            self.cancel_board_edit();
+            return Ok(());
+        }
+        db::rename_board(board.id, &name)?;
+        if board.id == self.board.id {
+            self.board.name = name;
+        }
+        self.reload_boards()?;
+        self.mode = AppMode::BoardPicker;
+        Ok(())
+    }
+    pub fn cancel_board_edit(&mut self) {
+        self.mode = AppMode::BoardPicker;
+    }
+    /// Asks to confirm deleting the selected board, and deletes it if this is
+    /// the second press in a row. If the board was open, another one is
+    /// opened. The last remaining board cannot be deleted.
+    pub fn remove_board(&mut self) -> AppResult<()> {
+        if self.boards.len() <= 1 {
+            return Ok(());
+        }
+        let board = self.boards[self.board_row].clone();
+        if self.remove_pending != Some(board.id) {
+            self.remove_pending = Some(board.id);
+            self.message = Some(format!(
+                "delete board '{}' and all its cards? press again to confirm",
+                board.name
+            ));
+            return Ok(());
+        }
+        self.remove_pending = None;
+        db::delete_board(board.id)?;
+        self.reload_boards()?;
+        if board.id == self.board.id {
+            self.dirty = false;
+            let next = self.boards[self.board_row].clone();
+            self.open_board(next)?;
+        }
+        Ok(())
+    }
+    pub fn cancel_remove_board(&mut self) {
+        self.remove_pending = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An app showing lists named after `titles`, each holding cards with
+    /// the given titles.
+    fn app_with(titles: &[(&str, &[&str])]) -> App {
+        let lists = titles
+            .iter()
+            .map(|(name, cards)| {
+                let cards = cards.iter().map(|&title| Card::new(title)).collect();
+                CardList::with_cards(name.to_string(), cards)
+            })
+            .collect();
+        App {
+            lists,
+            ..App::default()
+        }
+    }
+
+    fn titles(list: &CardList) -> Vec<&str> {
+        list.cards()
+            .iter()
+            .map(|card| card.title.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn drag_is_undone_in_one_step() {
+        let mut app = app_with(&[("Todo", &["a", "b"]), ("Doing", &[]), ("Done", &["c"])]);
+        app.start_drag();
+        app.drag_to(1, None);
+        app.drag_to(2, Some(0));
+        app.stop_drag();
+        assert_eq!(titles(&app.lists[0]), ["b"]);
+        assert_eq!(titles(&app.lists[2]), ["a", "c"]);
+        assert_eq!((app.col, app.row), (2, 0));
+
+        app.undo();
+        assert_eq!(titles(&app.lists[0]), ["a", "b"]);
+        assert_eq!(titles(&app.lists[2]), ["c"]);
+        assert!(!app.history.can_undo());
+    }
+
+    #[test]
+    fn drag_back_to_the_start_leaves_no_undo_step() {
+        let mut app = app_with(&[("Todo", &["a", "b"]), ("Done", &[])]);
+        app.start_drag();
+        app.drag_to(1, None);
+        app.drag_to(0, Some(0));
+        app.stop_drag();
+        assert_eq!(titles(&app.lists[0]), ["a", "b"]);
+        assert!(!app.history.can_undo());
+    }
+
+    #[test]
+    fn board_picker_scrolls_to_the_selected_board() {
+        let mut app = App::new();
+        app.boards = (0..30)
+            .map(|id| Board {
+                id,
+                name: format!("Board {id}"),
+            })
+            .collect();
+        assert_eq!(app.visible_boards(), 16);
+        for _ in 0..20 {
+            app.board_motion_down();
+        }
+        assert_eq!((app.board_row, app.board_offset), (20, 5));
+        // Moving back up only scrolls once the selection leaves the top
+        for _ in 0..15 {
+            app.board_motion_up();
+        }
+        assert_eq!((app.board_row, app.board_offset), (5, 5));
+        app.board_motion_up();
+        assert_eq!((app.board_row, app.board_offset), (4, 4));
+    }
+
+    #[test]
+    fn card_fields_are_undone_one_change_at_a_time() {
+        let mut app = app_with(&[("Todo", &["a"])]);
+        app.toggle_label(String::from("bug"));
+        app.set_assignee(Some(String::from("Sam")));
+        // Setting what is already set changes nothing
+        app.set_assignee(Some(String::from("Sam")));
+        assert_eq!(app.lists[0][0].labels, ["bug"]);
+
+        app.undo();
+        assert_eq!(app.lists[0][0].assignee, None);
+        app.undo();
+        assert!(app.lists[0][0].labels.is_empty());
+        assert!(!app.history.can_undo());
+    }
+
+    #[test]
+    fn description_is_edited_in_the_card_detail() {
+        let mut app = app_with(&[("Todo", &["a"])]);
+        app.open_card_detail();
+        app.edit_description();
+        assert_eq!(app.mode, AppMode::DescriptionEdit);
+        app.editor.insert_str("first");
+        app.editor.insert('\n');
+        app.editor.insert_str("second");
+        app.done_description_edit();
+        assert_eq!(app.mode, AppMode::CardDetail);
+        assert_eq!(app.lists[0][0].description, "first\nsecond");
+
+        // Cancelling keeps the description and leaves nothing to undo
+        app.edit_description();
+        app.editor.insert_str(" more");
+        app.cancel_description_edit();
+        assert_eq!(app.lists[0][0].description, "first\nsecond");
+        app.undo();
+        assert!(app.lists[0][0].description.is_empty());
+        assert!(!app.history.can_undo());
+    }
+
+    #[test]
+    fn moves_swap_with_cards_shown_by_the_search() {
+        let mut app = app_with(&[("Todo", &["a1", "b", "a2", "a3"])]);
+        app.search = Search::new("a", false);
+        app.move_down();
+        assert_eq!(titles(&app.lists[0]), ["a2", "b", "a1", "a3"]);
+        assert_eq!(app.row, 2);
+        app.move_up();
+        app.move_up();
+        assert_eq!(titles(&app.lists[0]), ["a1", "b", "a2", "a3"]);
+        assert_eq!(app.row, 0);
     }
 }
@@ -0,0 +1,557 @@
+use std::{
+    collections::HashSet,
+    env, fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{
+    app::{self, AppResult, Card, CardList},
+    command, config,
+    db::{self, Board},
+    json::{BoardData, Document},
+    markdown, todotxt, trello,
+};
+
+pub const USAGE: &str = "\
+Usage: kanban-tui [OPTIONS] [COMMAND]
+
+Commands:
+  add [--board <NAME>] [--list <LIST>] <TITLE>...
+          Add a card to the end of LIST [default: the first list] and print its id
+  move <ID> <LIST>
+          Move the card with id ID to the end of LIST on its board
+  ls [--board <NAME>] [--list <LIST>]
+          Print the id, list and title of every card, or those of LIST
+  lists [--board <NAME>]
+          Print the name and number of cards of every list
+  export [--board <NAME> | --all] [--format <FORMAT>] [PATH]
+          Write a board to PATH, or stdout. --all writes every board and
+          needs the json format, its default on stdout
+  import [--board <NAME>] [--format <FORMAT>] [--replace] [PATH]
+          Read boards from PATH, or stdin. A Markdown board is named after
+          --board, the # title of the file or the file name. Boards must not
+          exist yet unless --replace is given
+
+  --board defaults to the board the interface opens, except for import.
+
+  FORMAT is markdown, json, todo.txt, or trello for Trello board exports,
+  which can only be imported
+  [default: json for .json files, todo.txt for .txt files, else markdown]
+
+Options:
+      --db <PATH>      Database file to open
+                       [default: $KANBAN_TUI_DB or $XDG_DATA_HOME/kanban-tui/board.db]
+      --config <PATH>  Config file to read
+                       [default: $KANBAN_TUI_CONFIG or $XDG_CONFIG_HOME/kanban-tui/config.toml]
+  -h, --help           Print this help";
+
+/// Names of the subcommands, see [`Subcommand`].
+pub const SUBCOMMANDS: &[&str] = &["add", "move", "ls", "lists", "export", "import"];
+
+#[derive(Debug, Default)]
+pub struct Args {
+    pub db: Option<PathBuf>,
+    pub config: Option<PathBuf>,
+    pub help: bool,
+    /// Runs instead of the interface when given.
+    pub command: Option<Subcommand>,
+}
+
+/// A command run from the shell without opening the interface. A `None`
+/// path, given as `-` or left out, means stdin or stdout, and a `None` board
+/// the one the interface opens, see [`app::startup_board`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Subcommand {
+    Add {
+        board: Option<String>,
+        /// The first list when `None`.
+        list: Option<String>,
+        title: String,
+    },
+    Move {
+        id: i64,
+        list: String,
+    },
+    /// Lists the cards of a board, or of one of its lists.
+    Ls {
+        board: Option<String>,
+        list: Option<String>,
+    },
+    Lists {
+        board: Option<String>,
+    },
+    /// Writes one board, or every board with `all`.
+    Export {
+        board: Option<String>,
+        all: bool,
+        format: Format,
+        path: Option<PathBuf>,
+    },
+    Import {
+        board: Option<String>,
+        format: Format,
+        path: Option<PathBuf>,
+        replace: bool,
+    },
+}
+
+impl Args {
+    pub fn parse() -> AppResult<Self> {
+        Self::parse_from(env::args().skip(1))
+    }
+
+    pub fn parse_from(args: impl IntoIterator<Item = String>) -> AppResult<Self> {
+        let mut parsed = Self::default();
+        let mut command: Option<(String, Vec<String>)> = None;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => parsed.help = true,
+                "--db" => {
+                    let path = args.next().ok_or("--db requires a path")?;
+                    parsed.db = Some(PathBuf::from(path));
+                }
+                "--config" => {
+                    let path = args.next().ok_or("--config requires a path")?;
+                    parsed.config = Some(PathBuf::from(path));
+                }
+                _ => {
+                    if let Some(path) = arg.strip_prefix("--db=") {
+                        parsed.db = Some(PathBuf::from(path));
+                    } else if let Some(path) = arg.strip_prefix("--config=") {
+                        parsed.config = Some(PathBuf::from(path));
+                    } else if let Some((_, command_args)) = &mut command {
+                        command_args.push(arg);
+                    } else if SUBCOMMANDS.contains(&arg.as_str()) {
+                        command = Some((arg, Vec::new()));
+                    } else {
+                        return Err(format!("unexpected argument '{arg}'\n\n{USAGE}").into());
+                    }
+                }
+            }
+        }
+        if let Some((name, command_args)) = command {
+            parsed.command = Some(Subcommand::parse(&name, command_args)?);
+        }
+        Ok(parsed)
+    }
+
+    /// The database file these arguments select, see [`db::resolve_db_path`].
+    pub fn db_path(&self) -> PathBuf {
+        db::resolve_db_path(self.db.clone())
+    }
+
+    /// The config file these arguments select, see
+    /// [`config::resolve_config_path`].
+    pub fn config_path(&self) -> PathBuf {
+        config::resolve_config_path(self.config.clone())
+    }
+}
+
+impl Subcommand {
+    fn parse(name: &str, args: Vec<String>) -> AppResult<Self> {
+        let mut board = None;
+        let mut list = None;
+        let mut format = None;
+        let mut all = false;
+        let mut replace = false;
+        let mut positional = Vec::new();
+        let unexpected = |arg: &str| format!("unexpected argument '{arg}' for {name}\n\n{USAGE}");
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            let mut value = |what: &str| {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or(format!("{flag} requires {what}"))
+            };
+            match flag {
+                "--board" => board = Some(value("a name")?),
+                "--list" if matches!(name, "add" | "ls") => list = Some(value("a name")?),
+                "--format" if matches!(name, "export" | "import") => {
+                    format = Some(value("a format")?.parse()?)
+                }
+                "--all" if name == "export" => all = true,
+                "--replace" if name == "import" => replace = true,
+                // Everything after is positional, for titles starting with -
+                "--" => positional.extend(args.by_ref()),
+                "-" => positional.push(arg),
+                _ if arg.starts_with('-') => return Err(unexpected(&arg).into()),
+                _ => positional.push(arg),
+            }
+        }
+        Ok(match name {
+            "add" => {
+                let title = positional.join(" ");
+                if title.trim().is_empty() {
+                    return Err(format!("add requires a title\n\n{USAGE}").into());
+                }
+                Subcommand::Add { board, list, title }
+            }
+            "move" => {
+                if board.is_some() {
+                    return Err(unexpected("--board").into());
+                }
+                let [id, list] = <[String; 2]>::try_from(positional)
+                    .map_err(|_| format!("move requires a card id and a list\n\n{USAGE}"))?;
+                let id = id.parse().map_err(|_| format!("'{id}' is not a card id"))?;
+                Subcommand::Move { id, list }
+            }
+            "ls" | "lists" => {
+                if let Some(arg) = positional.first() {
+                    return Err(unexpected(arg).into());
+                }
+                match name {
+                    "ls" => Subcommand::Ls { board, list },
+                    _ => Subcommand::Lists { board },
+                }
+            }
+            _ => {
+                if let Some(arg) = positional.get(1) {
+                    return Err(unexpected(arg).into());
+                }
+                let path = positional
+                    .pop()
+                    .filter(|path| path != "-")
+                    .map(PathBuf::from);
+                let format = match format {
+                    Some(format) => format,
+                    None if all && path.is_none() => Format::Json,
+                    None => Format::from_path(path.as_deref()),
+                };
+                match name {
+                    "export" => {
+                        if all && board.is_some() {
+                            return Err("--all and --board cannot be used together".into());
+                        }
+                        if all && format != Format::Json {
+                            return Err("--all needs --format json".into());
+                        }
+                        Subcommand::Export {
+                            board,
+                            all,
+                            format,
+                            path,
+                        }
+                    }
+                    _ => Subcommand::Import {
+                        board,
+                        format,
+                        path,
+                        replace,
+                    },
+                }
+            }
+        })
+    }
+
+    /// Runs the command against the database chosen with
+    /// [`db::set_db_path`], printing its output, if any, to `out`.
+    pub fn run(self, out: &mut impl Write) -> AppResult<()> {
+        match self {
+            Subcommand::Add { board, list, title } => add(board.as_deref(), list.as_deref(), title),
+            Subcommand::Move { id, list } => move_card(id, &list),
+            Subcommand::Ls { board, list } => ls(board.as_deref(), list.as_deref(), out),
+            Subcommand::Lists { board } => lists(board.as_deref(), out),
+            Subcommand::Export {
+                board,
+                all,
+                format,
+                path,
+            } => export(board, all, format, path, out),
+            Subcommand::Import {
+                board,
+                format,
+                path,
+                replace,
+            } => import(board, format, path, replace),
+        }
+    }
+}
+
+/// The board called `name`, or the one the interface opens, and its lists.
+/// As in the interface, a board without lists is given one.
+fn read_board(name: Option<&str>) -> AppResult<(Board, Vec<CardList>)> {
+    let board = match name {
+        Some(name) => board_named(name)?,
+        None => app::startup_board()?,
+    };
+    let mut lists = db::read_lists(board.id)?;
+    if lists.is_empty() {
+        lists.push(CardList::new());
+        db::write_lists(board.id, &mut lists)?;
+    }
+    Ok((board, lists))
+}
+
+fn board_named(name: &str) -> AppResult<Board> {
+    Ok(db::find_board(name)?.ok_or(format!("no board named '{name}'"))?)
+}
+
+/// The list called `name` on a board, see [`command::find_list`].
+fn list_named(lists: &[CardList], name: &str, board: &str) -> AppResult<usize> {
+    command::find_list(lists, name).ok_or_else(|| {
+        let names: Vec<&str> = lists.iter().map(CardList::name).collect();
+        let names = names.join(", ");
+        format!("no list named '{name}' on board '{board}', it has {names}").into()
+    })
+}
+
+fn add(board: Option<&str>, list: Option<&str>, title: String) -> AppResult<()> {
+    let (board, lists) = read_board(board)?;
+    let col = match list {
+        Some(list) => list_named(&lists, list, &board.name)?,
+        None => 0,
+    };
+    let id = db::insert_card(board.id, col, &Card::new(title))?;
+    println!("{id}");
+    Ok(())
+}
+
+fn move_card(id: i64, list: &str) -> AppResult<()> {
+    let board_id = db::card_board(id)?.ok_or(format!("no card with id {id}"))?;
+    let board = db::read_boards()?
+        .into_iter()
+        .find(|board| board.id == board_id)
+        .ok_or(format!("no card with id {id}"))?;
+    let lists = db::read_lists(board.id)?;
+    let to = list_named(&lists, list, &board.name)?;
+    db::move_card(id, to)?;
+    Ok(())
+}
+
+/// Prints a card per line as its id, list and title, separated by tabs.
+fn ls(board: Option<&str>, list: Option<&str>, out: &mut impl Write) -> AppResult<()> {
+    let (board, lists) = read_board(board)?;
+    let shown = match list {
+        Some(list) => {
+            let col = list_named(&lists, list, &board.name)?;
+            &lists[col..=col]
+        }
+        None => &lists[..],
+    };
+    for list in shown {
+        for card in list.cards() {
+            let id = card.id.unwrap_or_default();
+            writeln!(out, "{id}\t{}\t{}", list.name(), card.title)?;
+        }
+    }
+    Ok(())
+}
+
+/// Prints a list per line as its name and number of cards, separated by a
+/// tab.
+fn lists(board: Option<&str>, out: &mut impl Write) -> AppResult<()> {
+    let (_, lists) = read_board(board)?;
+    for list in &lists {
+        writeln!(out, "{}\t{}", list.name(), list.len())?;
+    }
+    Ok(())
+}
+
+fn export(
+    board: Option<String>,
+    all: bool,
+    format: Format,
+    path: Option<PathBuf>,
+    out: &mut impl Write,
+) -> AppResult<()> {
+    let boards = || match &board {
+        _ if all => Ok(db::read_boards()?),
+        Some(name) => AppResult::Ok(vec![board_named(name)?]),
+        None => Ok(vec![app::startup_board()?]),
+    };
+    let text = match format {
+        Format::Markdown => {
+            let board = boards()?.remove(0);
+            markdown::to_markdown(&board.name, &db::read_lists(board.id)?)
+        }
+        Format::Json => {
+            let boards = boards()?
+                .iter()
+                .map(|board| Ok(BoardData::new(&board.name, &db::read_lists(board.id)?)))
+                .collect::<rusqlite::Result<_>>()?;
+            Document::new(boards).to_json()
+        }
+        Format::TodoTxt => {
+            let board = boards()?.remove(0);
+            todotxt::to_todotxt(&db::read_lists(board.id)?)
+        }
+        Format::Trello => return Err("trello is only supported by import".into()),
+    };
+    match path {
+        Some(path) => fs::write(&path, text).map_err(|e| format!("{}: {e}", path.display()))?,
+        None => out.write_all(text.as_bytes())?,
+    }
+    Ok(())
+}
+
+fn import(
+    board: Option<String>,
+    format: Format,
+    path: Option<PathBuf>,
+    replace: bool,
+) -> AppResult<()> {
+    let (source, text) = match &path {
+        Some(path) => {
+            let source = path.display().to_string();
+            let text = fs::read_to_string(path).map_err(|e| format!("{source}: {e}"))?;
+            (source, text)
+        }
+        None => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text)?;
+            (String::from("stdin"), text)
+        }
+    };
+    // What the import left out, printed once it is done
+    let mut notes = Vec::new();
+    let boards: Vec<(String, Vec<CardList>)> = match format {
+        Format::Markdown => {
+            let (title, lists) =
+                markdown::from_markdown(&text).map_err(|e| format!("{source}: {e}"))?;
+            let name = board
+                .or(title)
+                .or_else(|| file_stem(path.as_deref()))
+                .ok_or("the board needs a name, pass one with --board")?;
+            vec![(name, lists)]
+        }
+        Format::Json => {
+            let document = Document::from_json(&text).map_err(|e| format!("{source}: {e}"))?;
+            if board.is_some() && document.boards.len() != 1 {
+                return Err("--board needs a document with a single board".into());
+            }
+            document
+                .boards
+                .into_iter()
+                .map(|data| {
+                    (
+                        board.clone().unwrap_or(data.name.clone()),
+                        data.into_lists(),
+                    )
+                })
+                .collect()
+        }
+        Format::TodoTxt => {
+            let name = board
+                .or_else(|| file_stem(path.as_deref()))
+                .ok_or("the board needs a name, pass one with --board")?;
+            vec![(name, todotxt::from_todotxt(&text))]
+        }
+        Format::Trello => {
+            let trello = trello::from_trello(&text).map_err(|e| format!("{source}: {e}"))?;
+            if trello.archived_lists + trello.archived_cards > 0 {
+                notes.push(format!(
+                    "skipped {} archived lists and {} archived cards",
+                    trello.archived_lists, trello.archived_cards
+                ));
+            }
+            if !trello.unmapped.is_empty() {
+                notes.push(String::from("could not import:"));
+                notes.extend(trello.unmapped.iter().map(|what| format!("  {what}")));
+            }
+            vec![(board.unwrap_or(trello.name), trello.lists)]
+        }
+    };
+
+    // Check every board before writing any of them
+    let mut targets = Vec::with_capacity(boards.len());
+    for (index, (name, _)) in boards.iter().enumerate() {
+        if boards[..index].iter().any(|(other, _)| other == name) {
+            return Err(format!("board '{name}' appears twice in {source}").into());
+        }
+        match db::find_board(name)? {
+            Some(_) if !replace => {
+                return Err(format!(
+                    "board '{name}' already exists, pass --replace to overwrite it"
+                )
+                .into())
+            }
+            found => targets.push(found),
+        }
+    }
+    let mut ids = HashSet::new();
+    for ((name, mut lists), found) in boards.into_iter().zip(targets) {
+        let board = match found {
+            Some(board) => board,
+            None => db::insert_board(&name)?,
+        };
+        free_card_ids(board.id, &mut lists, &mut ids)?;
+        db::write_lists(board.id, &mut lists)?;
+        let cards: usize = lists.iter().map(|list| list.len()).sum();
+        println!(
+            "imported {} lists and {cards} cards into board '{name}'",
+            lists.len()
+        );
+    }
+    for note in notes {
+        println!("{note}");
+    }
+    Ok(())
+}
+
+/// The format of an export, picked with `--format` or else from the file
+/// extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Markdown,
+    Json,
+    /// A Trello board export, which can only be imported.
+    Trello,
+    TodoTxt,
+}
+
+impl Format {
+    fn from_path(path: Option<&Path>) -> Self {
+        match path.and_then(Path::extension) {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => Format::Json,
+            Some(extension) if extension.eq_ignore_ascii_case("txt") => Format::TodoTxt,
+            _ => Format::Markdown,
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "markdown" | "md" => Ok(Format::Markdown),
+            "json" => Ok(Format::Json),
+            "trello" => Ok(Format::Trello),
+            "todo.txt" | "todotxt" => Ok(Format::TodoTxt),
+            _ => Err(format!(
+                "unknown format '{s}', expected markdown, json, trello or todo.txt"
+            )),
+        }
+    }
+}
+
+fn file_stem(path: Option<&Path>) -> Option<String> {
+    let stem = path?.file_stem()?.to_string_lossy();
+    Some(stem.into_owned())
+}
+
+/// Clears the ids of imported cards that belong to another board, or to a
+/// card imported before, so that they get new ones when saved.
+fn free_card_ids(board_id: i64, lists: &mut [CardList], seen: &mut HashSet<i64>) -> AppResult<()> {
+    for list in lists {
+        for row in 0..list.len() {
+            let card = &mut list[row];
+            let Some(id) = card.id else {
+                continue;
+            };
+            let owner = db::card_board(id)?;
+            if !seen.insert(id) || owner.is_some_and(|owner| owner != board_id) {
+                card.id = None;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,325 @@
+use std::{path::PathBuf, str::FromStr};
+
+use chrono::NaiveDate;
+
+use crate::{
+    app::{App, AppResult, CardList, SortKey},
+    markdown,
+};
+
+/// The commands of the `:` prompt, with a description of their arguments.
+pub const COMMANDS: &[(&str, &str)] = &[
+    ("newlist", "<name>"),
+    ("rename", "<name>"),
+    ("move", "<list>"),
+    ("sort", "[title|created|updated|due]"),
+    ("due", "[YYYY-MM-DD]"),
+    ("label", "<name>"),
+    ("assign", "[name]"),
+    ("board", "<name>"),
+    ("export", "<path>"),
+    ("import", "<path>"),
+    ("w", ""),
+    ("q", ""),
+    ("wq", ""),
+];
+
+/// A command typed at the `:` prompt.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Adds a list after the selected one.
+    NewList(String),
+    /// Renames the selected list.
+    Rename(String),
+    /// Moves the selected card to the end of another list.
+    Move(String),
+    /// Sorts the cards of the selected list.
+    Sort(SortKey),
+    /// Sets the due date of the selected card, or clears it.
+    Due(Option<NaiveDate>),
+    /// Adds a label to the selected card, or removes it if the card has it.
+    Label(String),
+    /// Sets who the selected card is assigned to, or clears it.
+    Assign(Option<String>),
+    /// Opens a board, creating it if needed.
+    Board(String),
+    /// Writes the board to a Markdown file.
+    Export(PathBuf),
+    /// Replaces the lists of the board with those of a Markdown file.
+    Import(PathBuf),
+    Write,
+    Quit,
+    WriteQuit,
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().trim_start_matches(':');
+        let (name, arg) = s.split_once(char::is_whitespace).unwrap_or((s, ""));
+        let arg = arg.trim();
+        let required = |arg: &str| match arg {
+            "" => Err(usage(name)),
+            arg => Ok(arg.to_string()),
+        };
+        let none = |command| match arg {
+            "" => Ok(command),
+            _ => Err(usage(name)),
+        };
+        match name {
+            "newlist" => required(arg).map(Command::NewList),
+            "rename" => required(arg).map(Command::Rename),
+            "move" => required(arg).map(Command::Move),
+            "sort" if arg.is_empty() => Ok(Command::Sort(SortKey::Title)),
+            "sort" => arg.parse().map(Command::Sort).map_err(|_| usage(name)),
+            "due" if arg.is_empty() => Ok(Command::Due(None)),
+            "due" => NaiveDate::parse_from_str(arg, "%Y-%m-%d")
+                .map(|due| Command::Due(Some(due)))
+                .map_err(|_| usage(name)),
+            "label" => required(arg.trim_start_matches('#')).map(Command::Label),
+            "assign" => Ok(Command::Assign((!arg.is_empty()).then(|| arg.to_string()))),
+            "board" => required(arg).map(Command::Board),
+            "export" => required(arg).map(|path| Command::Export(PathBuf::from(path))),
+            "import" => required(arg).map(|path| Command::Import(PathBuf::from(path))),
+            "w" | "write" => none(Command::Write),
+            "q" | "quit" => none(Command::Quit),
+            "wq" | "x" => none(Command::WriteQuit),
+            "" => Err(String::from("no command given")),
+            _ => Err(format!("unknown command: {name}")),
+        }
+    }
+}
+
+fn usage(name: &str) -> String {
+    match COMMANDS.iter().find(|(command, _)| *command == name) {
+        Some((command, args)) => format!("usage: :{command} {args}"),
+        None => format!("unknown command: {name}"),
+    }
+}
+
+impl Command {
+    /// Runs the command on `app`, returning a message for the footer.
+    pub fn run(self, app: &mut App) -> AppResult<Option<String>> {
+        match self {
+            Command::NewList(name) => app.add_list(name),
+            Command::Rename(name) => app.rename_list(name),
+            Command::Move(name) => {
+                let Some(col) = find_list(app.lists(), &name) else {
+                    return Ok(Some(format!("no list named {name}")));
+                };
+                if app.card().is_none() {
+                    return Ok(Some(String::from("no card selected")));
+                }
+                app.move_card_to(col);
+            }
+            Command::Sort(key) => app.sort_cards(key),
+            Command::Due(_) | Command::Label(_) | Command::Assign(_) if app.card().is_none() => {
+                return Ok(Some(String::from("no card selected")));
+            }
+            Command::Due(due) => app.set_due(due),
+            Command::Label(label) => app.toggle_label(label),
+            Command::Assign(assignee) => app.set_assignee(assignee),
+            Command::Board(name) => app.open_board_named(&name)?,
+            Command::Export(path) => {
+                markdown::export(&path, &app.board().name, app.lists())?;
+                return Ok(Some(format!("exported to {}", path.display())));
+            }
+            Command::Import(path) => {
+                let (_, lists) = markdown::import(&path)?;
+                let cards: usize = lists.iter().map(|list| list.len()).sum();
+                app.replace_lists(lists);
+                return Ok(Some(format!(
+                    "imported {cards} cards from {}",
+                    path.display()
+                )));
+            }
+            Command::Write => {
+                app.save()?;
+                return Ok(Some(format!("saved to {}", app.db_path().display())));
+            }
+            Command::Quit => app.quit(),
+            Command::WriteQuit => {
+                app.save()?;
+                app.quit();
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// The list called `name`, or if there is none, called `name` ignoring
+/// case.
+pub fn find_list(lists: &[CardList], name: &str) -> Option<usize> {
+    lists
+        .iter()
+        .position(|list| list.name() == name)
+        .or_else(|| {
+            lists
+                .iter()
+                .position(|list| list.name().eq_ignore_ascii_case(name))
+        })
+}
+
+/// Tab completion of the word at the end of `input`: command names, then
+/// list, board, sort key, label or assignee names depending on the command.
+///
+/// Returns the completed input and, if the completion is ambiguous, the
+/// candidates left.
+pub fn complete(input: &str, app: &App) -> (String, Vec<String>) {
+    let (head, word, candidates): (String, &str, Vec<String>) =
+        match input.split_once(char::is_whitespace) {
+            None => {
+                let names = COMMANDS.iter().map(|(name, _)| name.to_string());
+                (String::new(), input, names.collect())
+            }
+            Some((name, arg)) => {
+                let word = arg.trim_start();
+                let head = format!("{name} ");
+                let candidates = match name {
+                    "move" => app.lists().iter().map(|l| l.name().to_string()).collect(),
+                    "rename" => vec![app.list().name().to_string()],
+                    "board" => app.boards().iter().map(|b| b.name.clone()).collect(),
+                    "sort" => SortKey::NAMES.iter().map(|key| key.to_string()).collect(),
+                    "label" => app.labels(),
+                    "assign" => app.assignees(),
+                    _ => Vec::new(),
+                };
+                (head, word, candidates)
+            }
+        };
+    let lower = word.to_lowercase();
+    let matching: Vec<String> = candidates
+        .into_iter()
+        .filter(|candidate| candidate.to_lowercase().starts_with(&lower))
+        .collect();
+    match matching.as_slice() {
+        [] => (input.to_string(), matching),
+        [only] => {
+            // Ready for the argument if the command takes one
+            let takes_arg = head.is_empty()
+                && COMMANDS
+                    .iter()
+                    .any(|(name, args)| name == only && !args.is_empty());
+            let space = if takes_arg { " " } else { "" };
+            (format!("{head}{only}{space}"), Vec::new())
+        }
+        [first, rest @ ..] => {
+            let common = rest.iter().fold(first.as_str(), |common, candidate| {
+                let len = common
+                    .char_indices()
+                    .zip(candidate.chars())
+                    .find(|((_, a), b)| a != b)
+                    .map_or(common.len(), |((i, _), _)| i);
+                &common[..len]
+            });
+            let word = if common.len() > word.len() {
+                common
+            } else {
+                word
+            };
+            (format!("{head}{word}"), matching)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Result<Command, String> {
+        input.parse()
+    }
+
+    #[test]
+    fn parses_commands_and_aliases() {
+        assert_eq!(
+            parse(":newlist To do"),
+            Ok(Command::NewList("To do".into()))
+        );
+        assert_eq!(parse("  move  Done "), Ok(Command::Move("Done".into())));
+        assert_eq!(parse("export a b.md"), Ok(Command::Export("a b.md".into())));
+        assert_eq!(parse("write"), Ok(Command::Write));
+        assert_eq!(parse("quit"), Ok(Command::Quit));
+        assert_eq!(parse("x"), Ok(Command::WriteQuit));
+    }
+
+    #[test]
+    fn reports_usage_errors() {
+        assert_eq!(parse("newlist"), Err("usage: :newlist <name>".into()));
+        assert_eq!(parse("move "), Err("usage: :move <list>".into()));
+        assert_eq!(parse("q now"), Err("usage: :q ".into()));
+        assert_eq!(
+            parse("sort size"),
+            Err("usage: :sort [title|created|updated|due]".into())
+        );
+        assert_eq!(parse("delete"), Err("unknown command: delete".into()));
+        assert_eq!(parse(":"), Err("no command given".into()));
+    }
+
+    #[test]
+    fn parses_sort_keys() {
+        assert_eq!(parse("sort"), Ok(Command::Sort(SortKey::Title)));
+        assert_eq!(parse("sort title"), Ok(Command::Sort(SortKey::Title)));
+        assert_eq!(parse("sort created"), Ok(Command::Sort(SortKey::Created)));
+        assert_eq!(parse("sort updated"), Ok(Command::Sort(SortKey::Updated)));
+        assert_eq!(parse("sort due"), Ok(Command::Sort(SortKey::Due)));
+    }
+
+    #[test]
+    fn parses_card_fields() {
+        let due = NaiveDate::from_ymd_opt(2024, 3, 1);
+        assert_eq!(parse("due 2024-03-01"), Ok(Command::Due(due)));
+        assert_eq!(parse("due"), Ok(Command::Due(None)));
+        assert_eq!(parse("due friday"), Err("usage: :due [YYYY-MM-DD]".into()));
+        assert_eq!(parse("label #bug"), Ok(Command::Label("bug".into())));
+        assert_eq!(parse("label #"), Err("usage: :label <name>".into()));
+        let assignee = Some(String::from("Sam Lee"));
+        assert_eq!(parse("assign Sam Lee"), Ok(Command::Assign(assignee)));
+        assert_eq!(parse("assign"), Ok(Command::Assign(None)));
+    }
+
+    #[test]
+    fn sets_card_fields_on_the_selected_card() {
+        let mut app = App::new();
+        for input in ["due 2024-03-01", "label bug", "label ui", "assign Sam"] {
+            assert_eq!(parse(input).unwrap().run(&mut app).unwrap(), None);
+        }
+        parse("label bug").unwrap().run(&mut app).unwrap();
+        let card = app.card().unwrap();
+        assert_eq!(card.due, NaiveDate::from_ymd_opt(2024, 3, 1));
+        assert_eq!(card.labels, ["ui"]);
+        assert_eq!(card.assignee.as_deref(), Some("Sam"));
+        assert_eq!(complete("label ", &app), ("label ui".into(), Vec::new()));
+
+        app.select(0, Some(1));
+        parse("assign").unwrap().run(&mut app).unwrap();
+        assert_eq!(complete("assign ", &app), ("assign Sam".into(), Vec::new()));
+    }
+
+    #[test]
+    fn completes_a_single_match() {
+        let app = App::new();
+        assert_eq!(complete("ne", &app), ("newlist ".into(), Vec::new()));
+        assert_eq!(complete("wq", &app), ("wq".into(), Vec::new()));
+        assert_eq!(
+            complete("sort u", &app),
+            ("sort updated".into(), Vec::new())
+        );
+        assert_eq!(complete("move l", &app), ("move List 1".into(), Vec::new()));
+        assert_eq!(complete("zzz", &app), ("zzz".into(), Vec::new()));
+    }
+
+    #[test]
+    fn completes_to_the_common_prefix() {
+        let mut app = App::new();
+        app.add_list("List 2".into());
+        let (input, candidates) = complete("move L", &app);
+        assert_eq!(input, "move List ");
+        assert_eq!(candidates, ["List 1", "List 2"]);
+
+        let (input, candidates) = complete("w", &app);
+        assert_eq!(input, "w");
+        assert_eq!(candidates, ["w", "wq"]);
+    }
+}
@@ -0,0 +1,80 @@
+use std::{
+    collections::BTreeMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{app::AppResult, keymap::KeymapConfig, theme::ThemeConfig};
+
+/// Environment variable overriding the default config file location.
+pub const CONFIG_ENV_VAR: &str = "KANBAN_TUI_CONFIG";
+
+/// The contents of the config file. Every table is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Name of the theme to use, built in or from `themes`.
+    pub theme: Option<String>,
+    pub themes: BTreeMap<String, ThemeConfig>,
+    pub keys: KeymapConfig,
+}
+
+impl Config {
+    /// Reads the config file at `path`, falling back to the defaults if it
+    /// does not exist.
+    pub fn load(path: &Path) -> AppResult<Self> {
+        match fs::read_to_string(path) {
+            Ok(contents) => {
+                toml::from_str(&contents).map_err(|e| format!("{}: {e}", path.display()).into())
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("{}: {e}", path.display()).into()),
+        }
+    }
+}
+
+/// Picks the config file: an explicit path (from `--config`) wins over
+/// `KANBAN_TUI_CONFIG`, which wins over
+/// `$XDG_CONFIG_HOME/kanban-tui/config.toml`.
+pub fn resolve_config_path(explicit: Option<PathBuf>) -> PathBuf {
+    if let Some(path) = explicit {
+        return path;
+    }
+    if let Some(path) = env::var_os(CONFIG_ENV_VAR).filter(|path| !path.is_empty()) {
+        return PathBuf::from(path);
+    }
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+        .join("kanban-tui")
+        .join("config.toml")
+}
+
+/// The XDG base directory named by the environment variable `var`, or
+/// `default_suffix` under the home directory when it is unset or empty.
+pub fn xdg_dir(var: &str, default_suffix: &str) -> PathBuf {
+    env::var_os(var)
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(default_suffix)))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xdg_dir_falls_back_to_the_home_directory() {
+        // A variable of its own, as tests share the environment
+        let var = "KANBAN_TUI_TEST_XDG_HOME";
+        env::set_var(var, "/xdg");
+        assert_eq!(xdg_dir(var, ".local/share"), Path::new("/xdg"));
+
+        env::set_var(var, "");
+        if let Some(home) = env::var_os("HOME") {
+            let expected = Path::new(&home).join(".local/share");
+            assert_eq!(xdg_dir(var, ".local/share"), expected);
+        }
+    }
+}
@@ -1,62 +1,82 @@
-use rusqlite::{Connection, Result};
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        OnceLock,
+    },
+};
 
-#[derive(Debug)]
-pub struct User {
-    pub id: i32,
-    pub name: String,
-}
+use chrono::{DateTime, NaiveDate, Utc};
+use rusqlite::{
+    ffi, params, Connection, OptionalExtension, Result, Row, Statement, Transaction,
+    TransactionBehavior,
+};
 
-#[derive(Debug)]
-pub struct Board {
-    pub id: i32,
-    pub name: String
-}
+use crate::{
+    app::{Card, CardList, ChecklistItem},
+    config,
+};
 
-pub fn create_db() -> Result<()> {
-    // Open a database connection
-    // let conn = Connection::open("example.db")?;
-    let conn = open_db()?;
+/// Name of the board a database without boards starts with.
+pub const DEFAULT_BOARD: &str = "Default";
 
-    // Create a table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS users (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            name TEXT NOT NULL,
-            email TEXT UNIQUE NOT NULL
-        )",
-        [],
-    )?;
+/// Environment variable overriding the default database location.
+pub const DB_ENV_VAR: &str = "KANBAN_TUI_DB";
 
-    // Query the database
-    let mut stmt = conn.prepare("SELECT id, name FROM cards")?;
-    let user_iter = stmt.query_map([], |row| {
-        Ok(User {
-            id: row.get(0)?,
-            name: row.get(1)?,
-        })
-    })?;
+/// Where the database was kept before it moved to the data directory,
+/// relative to the working directory.
+pub const LEGACY_DB_PATH: &str = "example.db";
 
-    // println!("User: {} ({})", row.get(0), row.get(1));
-    // println!("{:?}", user_iter);
-    for user in user_iter {
-        println!("{:?}", user.unwrap());
+static DB_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Whether [`migrate`] already ran. The database path is fixed once it is
+/// first used, so migrating it once per process is enough.
+static MIGRATED: AtomicBool = AtomicBool::new(false);
+
+/// Picks the database file: an explicit path (from `--db`) wins over
+/// `KANBAN_TUI_DB`, which wins over `$XDG_DATA_HOME/kanban-tui/board.db`.
+///
+/// Until that file exists, a [`LEGACY_DB_PATH`] in the working directory is
+/// used instead, with a notice on stderr, so that boards kept there before
+/// the data directory was used do not seem lost.
+pub fn resolve_db_path(explicit: Option<PathBuf>) -> PathBuf {
+    if let Some(path) = explicit {
+        return path;
+    }
+    if let Some(path) = env::var_os(DB_ENV_VAR).filter(|path| !path.is_empty()) {
+        return PathBuf::from(path);
     }
+    let path = config::xdg_dir("XDG_DATA_HOME", ".local/share")
+        .join("kanban-tui")
+        .join("board.db");
+    let legacy = Path::new(LEGACY_DB_PATH);
+    if !path.exists() && legacy.exists() {
+        eprintln!(
+            "note: using ./{LEGACY_DB_PATH}, move it to {} to use it from any directory",
+            path.display()
+        );
+        return legacy.to_path_buf();
+    }
+    path
+}
 
-    Ok(())
+/// Sets the database file used by every function in this module. Only the
+/// first call has an effect; without one the path from
+/// [`resolve_db_path`] with no explicit override is used.
+pub fn set_db_path(path: PathBuf) {
+    let _ = DB_PATH.set(path);
 }
 
-pub fn read_user_data(name: &str) -> Result<User> {
-    let conn = open_db()?;
-    // Query the database
-    let mut stmt = conn.prepare("SELECT id, name FROM cards WHERE name = ?")?;
-    let user_iter = stmt.query_row([name], |row| {
-        Ok(User {
-            id: row.get(0)?,
-            name: row.get(1)?,
-        })
-    })?;
+pub fn db_path() -> &'static Path {
+    DB_PATH.get_or_init(|| resolve_db_path(None))
+}
 
-    Ok(user_iter)
+#[derive(Debug, Clone)]
+pub struct Board {
+    pub id: i64,
+    pub name: String,
 }
 
 pub fn read_board_name(name: &str) -> Result<Board> {
@@ -73,17 +93,640 @@ pub fn read_board_name(name: &str) -> Result<Board> {
     Ok(board_name)
 }
 
-pub fn insert_user_data(name: &str, email: &str) -> Result<usize, rusqlite::Error> {
-    // Insert data into the table
-    println!("inserting ");
+/// Looks up a board by name, returning `None` if it does not exist yet.
+pub fn find_board(name: &str) -> Result<Option<Board>> {
+    read_board_name(name).optional()
+}
+
+pub fn insert_board(name: &str) -> Result<Board> {
+    let conn = open_db()?;
+    conn.execute("INSERT INTO boards (name) VALUES (?)", [name])?;
+    Ok(Board {
+        id: conn.last_insert_rowid(),
+        name: name.to_string(),
+    })
+}
+
+/// Reads every board, ordered by name.
+pub fn read_boards() -> Result<Vec<Board>> {
+    let conn = open_db()?;
+    let mut stmt = conn.prepare("SELECT id, name FROM boards ORDER BY name COLLATE NOCASE")?;
+    let boards = stmt
+        .query_map([], |row| {
+            Ok(Board {
+                id: row.get(0)?,
+                name: row.get(1)?,
+            })
+        })?
+        .collect();
+    boards
+}
+
+/// The board opened most recently, see [`mark_opened`], or else the first
+/// by name. `None` if there are no boards.
+pub fn last_opened_board() -> Result<Option<Board>> {
+    let conn = open_db()?;
+    conn.query_row(
+        "SELECT id, name FROM boards ORDER BY opened DESC, name COLLATE NOCASE LIMIT 1",
+        [],
+        |row| {
+            Ok(Board {
+                id: row.get(0)?,
+                name: row.get(1)?,
+            })
+        },
+    )
+    .optional()
+}
+
+/// Records that a board was opened in the interface, for
+/// [`last_opened_board`].
+pub fn mark_opened(id: i64) -> Result<()> {
     let conn = open_db()?;
     conn.execute(
-        "INSERT INTO users (name, email) VALUES (?, ?)",
-        (name, email)
+        "UPDATE boards SET opened = (SELECT MAX(opened) + 1 FROM boards) WHERE id = ?",
+        [id],
+    )?;
+    Ok(())
+}
+
+pub fn rename_board(id: i64, name: &str) -> Result<()> {
+    let conn = open_db()?;
+    conn.execute("UPDATE boards SET name = ? WHERE id = ?", params![name, id])?;
+    Ok(())
+}
+
+/// Deletes a board together with its lists and cards.
+pub fn delete_board(id: i64) -> Result<()> {
+    let conn = open_db()?;
+    conn.execute("DELETE FROM boards WHERE id = ?", [id])?;
+    Ok(())
+}
+
+/// The board holding the card with database id `id`, if any.
+pub fn card_board(id: i64) -> Result<Option<i64>> {
+    let conn = open_db()?;
+    conn.query_row(
+        "SELECT lists.board_id FROM cards JOIN lists ON lists.id = cards.list_id
+         WHERE cards.id = ?",
+        [id],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// The revision of a board, which every write to its lists and cards
+/// increments, so that a board read earlier can tell whether it is stale.
+pub fn read_revision(board_id: i64) -> Result<i64> {
+    let conn = open_db()?;
+    board_revision(&conn, board_id)
+}
+
+fn board_revision(conn: &Connection, board_id: i64) -> Result<i64> {
+    conn.query_row(
+        "SELECT revision FROM boards WHERE id = ?",
+        [board_id],
+        |row| row.get(0),
     )
 }
 
+fn bump_revision(conn: &Connection, board_id: i64) -> Result<i64> {
+    conn.execute(
+        "UPDATE boards SET revision = revision + 1 WHERE id = ?",
+        [board_id],
+    )?;
+    board_revision(conn, board_id)
+}
+
+/// Appends `card` to the list at `list_pos` on a board, returning the id it
+/// gets.
+pub fn insert_card(board_id: i64, list_pos: usize, card: &Card) -> Result<i64> {
+    let mut conn = open_db()?;
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let list_id: i64 = tx.query_row(
+        "SELECT id FROM lists WHERE board_id = ? ORDER BY position LIMIT 1 OFFSET ?",
+        params![board_id, list_pos as i64],
+        |row| row.get(0),
+    )?;
+    tx.execute(
+        "INSERT INTO cards
+         (list_id, title, description, created_at, updated_at, due, assignee, position)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7,
+            (SELECT COALESCE(MAX(position) + 1, 0) FROM cards WHERE list_id = ?1))",
+        params![
+            list_id,
+            card.title,
+            card.description,
+            card.created_at.timestamp(),
+            card.updated_at.timestamp(),
+            card.due.map(|due| due.to_string()),
+            card.assignee,
+        ],
+    )?;
+    let card_id = tx.last_insert_rowid();
+    for (label_pos, label) in card.labels.iter().enumerate() {
+        tx.execute(
+            "INSERT INTO card_labels (card_id, name, position) VALUES (?, ?, ?)",
+            params![card_id, label, label_pos as i64],
+        )?;
+    }
+    for (item_pos, item) in card.checklist.iter().enumerate() {
+        tx.execute(
+            "INSERT INTO checklist_items (card_id, text, done, position) VALUES (?, ?, ?, ?)",
+            params![card_id, item.text, item.done, item_pos as i64],
+        )?;
+    }
+    bump_revision(&tx, board_id)?;
+    tx.commit()?;
+    Ok(card_id)
+}
+
+/// Moves the card with id `id` to the end of the list at `list_pos` on its
+/// board, marking it updated. Cards already in that list stay where they
+/// are.
+pub fn move_card(id: i64, list_pos: usize) -> Result<()> {
+    let mut conn = open_db()?;
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let (board_id, list_id): (i64, i64) = tx.query_row(
+        "SELECT lists.board_id, lists.id FROM lists
+         WHERE board_id = (SELECT board_id FROM lists JOIN cards ON cards.list_id = lists.id
+                           WHERE cards.id = ?1)
+         ORDER BY position LIMIT 1 OFFSET ?2",
+        params![id, list_pos as i64],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let moved = tx.execute(
+        "UPDATE cards SET list_id = ?1, updated_at = ?2,
+            position = (SELECT COALESCE(MAX(position) + 1, 0) FROM cards WHERE list_id = ?1)
+         WHERE id = ?3 AND list_id != ?1",
+        params![list_id, Utc::now().timestamp(), id],
+    )?;
+    if moved > 0 {
+        bump_revision(&tx, board_id)?;
+    }
+    tx.commit()
+}
+
+/// Reads every list of a board together with its cards, ordered by position.
+pub fn read_lists(board_id: i64) -> Result<Vec<CardList>> {
+    let conn = open_db()?;
+    let mut list_stmt =
+        conn.prepare("SELECT id, name FROM lists WHERE board_id = ? ORDER BY position")?;
+    let mut card_stmt = conn.prepare(
+        "SELECT id, title, description, created_at, updated_at, due, assignee
+         FROM cards WHERE list_id = ? ORDER BY position",
+    )?;
+    let mut label_stmt =
+        conn.prepare("SELECT name FROM card_labels WHERE card_id = ? ORDER BY position")?;
+    let mut checklist_stmt = conn.prepare(
+        "SELECT text, done FROM checklist_items WHERE card_id = ? ORDER BY position",
+    )?;
+
+    let rows = list_stmt
+        .query_map([board_id], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut lists = Vec::with_capacity(rows.len());
+    for (list_id, name) in rows {
+        let mut cards = card_stmt
+            .query_map([list_id], card_from_row)?
+            .collect::<Result<Vec<Card>>>()?;
+        for card in &mut cards {
+            read_card_details(&mut label_stmt, &mut checklist_stmt, card)?;
+        }
+        lists.push(CardList::with_cards(name, cards));
+    }
+    Ok(lists)
+}
+
+/// Fills in the labels and checklist of `card` from the statements prepared
+/// by [`read_lists`].
+fn read_card_details(
+    label_stmt: &mut Statement,
+    checklist_stmt: &mut Statement,
+    card: &mut Card,
+) -> Result<()> {
+    card.labels = label_stmt
+        .query_map([card.id], |row| row.get(0))?
+        .collect::<Result<_>>()?;
+    card.checklist = checklist_stmt
+        .query_map([card.id], |row| {
+            Ok(ChecklistItem {
+                text: row.get(0)?,
+                done: row.get(1)?,
+            })
+        })?
+        .collect::<Result<_>>()?;
+    Ok(())
+}
+
+fn card_from_row(row: &Row) -> Result<Card> {
+    let due: Option<String> = row.get(5)?;
+    Ok(Card {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        description: row.get(2)?,
+        created_at: DateTime::from_timestamp(row.get(3)?, 0).unwrap_or_default(),
+        updated_at: DateTime::from_timestamp(row.get(4)?, 0).unwrap_or_default(),
+        due: due.and_then(|due| NaiveDate::parse_from_str(&due, "%Y-%m-%d").ok()),
+        labels: Vec::new(),
+        assignee: row.get(6)?,
+        checklist: Vec::new(),
+    })
+}
+
+/// A card as stored, with the list and position it is stored at.
+struct StoredCard {
+    list_id: i64,
+    position: i64,
+    card: Card,
+}
+
+/// Whether the `cards` row of `card` would differ from that of `stored`,
+/// leaving aside its list and position.
+fn card_changed(stored: &Card, card: &Card) -> bool {
+    stored.title != card.title
+        || stored.description != card.description
+        || stored.created_at.timestamp() != card.created_at.timestamp()
+        || stored.updated_at.timestamp() != card.updated_at.timestamp()
+        || stored.due != card.due
+        || stored.assignee != card.assignee
+}
+
+/// Replaces the stored lists and cards of a board with `lists`.
+///
+/// Positions are taken from the order of the slices, so whatever order the
+/// board has in memory is the order it is loaded back in. Cards keep their
+/// ids; cards saved for the first time get theirs assigned here. Only the
+/// rows that differ from what is stored are written. Returns the new
+/// revision of the board, see [`read_revision`].
+pub fn write_lists(board_id: i64, lists: &mut [CardList]) -> Result<i64> {
+    let mut conn = open_db()?;
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let revision = write_board(&tx, board_id, lists)?;
+    tx.commit()?;
+    Ok(revision)
+}
+
+/// Like [`write_lists`], but only if the board is still at `revision`, as
+/// when it was read. Returns `None` without writing anything if another
+/// process changed the board since.
+pub fn write_lists_at(
+    board_id: i64,
+    revision: i64,
+    lists: &mut [CardList],
+) -> Result<Option<i64>> {
+    let mut conn = open_db()?;
+    // Taking the write lock up front keeps the check and the write together
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    if board_revision(&tx, board_id)? != revision {
+        return Ok(None);
+    }
+    let revision = write_board(&tx, board_id, lists)?;
+    tx.commit()?;
+    Ok(Some(revision))
+}
+
+/// Writes the lists of a board for [`write_lists`], returning its new
+/// revision.
+fn write_board(tx: &Transaction, board_id: i64, lists: &mut [CardList]) -> Result<i64> {
+    let stored_lists = tx
+        .prepare("SELECT id, name FROM lists WHERE board_id = ? ORDER BY position")?
+        .query_map([board_id], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    let mut stored_cards = HashMap::new();
+    {
+        let mut card_stmt = tx.prepare(
+            "SELECT cards.id, title, description, created_at, updated_at, due, assignee,
+             list_id, cards.position
+             FROM cards JOIN lists ON lists.id = cards.list_id WHERE board_id = ?",
+        )?;
+        let mut label_stmt =
+            tx.prepare("SELECT name FROM card_labels WHERE card_id = ? ORDER BY position")?;
+        let mut checklist_stmt = tx.prepare(
+            "SELECT text, done FROM checklist_items WHERE card_id = ? ORDER BY position",
+        )?;
+        let rows = card_stmt
+            .query_map([board_id], |row| {
+                Ok(StoredCard {
+                    list_id: row.get(7)?,
+                    position: row.get(8)?,
+                    card: card_from_row(row)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        for mut stored in rows {
+            read_card_details(&mut label_stmt, &mut checklist_stmt, &mut stored.card)?;
+            stored_cards.insert(stored.card.id, stored);
+        }
+    }
+
+    let mut insert_list_stmt =
+        tx.prepare("INSERT INTO lists (board_id, name, position) VALUES (?, ?, ?)")?;
+    let mut rename_list_stmt = tx.prepare("UPDATE lists SET name = ? WHERE id = ?")?;
+    let mut insert_card_stmt = tx.prepare(
+        "INSERT INTO cards
+         (id, list_id, title, description, created_at, updated_at, due, assignee, position)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )?;
+    let mut update_card_stmt = tx.prepare(
+        "UPDATE cards SET list_id = ?, title = ?, description = ?, created_at = ?,
+         updated_at = ?, due = ?, assignee = ?, position = ? WHERE id = ?",
+    )?;
+    let mut delete_labels_stmt = tx.prepare("DELETE FROM card_labels WHERE card_id = ?")?;
+    let mut label_stmt =
+        tx.prepare("INSERT INTO card_labels (card_id, name, position) VALUES (?, ?, ?)")?;
+    let mut delete_checklist_stmt =
+        tx.prepare("DELETE FROM checklist_items WHERE card_id = ?")?;
+    let mut checklist_stmt = tx.prepare(
+        "INSERT INTO checklist_items (card_id, text, done, position) VALUES (?, ?, ?, ?)",
+    )?;
+    for (list_pos, list) in lists.iter_mut().enumerate() {
+        // Lists have no ids in memory, so they are matched by position
+        let list_id = match stored_lists.get(list_pos) {
+            Some((id, name)) => {
+                if name != list.name() {
+                    rename_list_stmt.execute(params![list.name(), id])?;
+                }
+                *id
+            }
+            None => {
+                insert_list_stmt.execute(params![board_id, list.name(), list_pos as i64])?;
+                tx.last_insert_rowid()
+            }
+        };
+        for card_pos in 0..list.len() {
+            let card = &mut list[card_pos];
+            let stored = card.id.and_then(|id| stored_cards.remove(&Some(id)));
+            let card_id = match &stored {
+                Some(stored) => {
+                    let card_id = card.id.unwrap_or_default();
+                    if stored.list_id != list_id
+                        || stored.position != card_pos as i64
+                        || card_changed(&stored.card, card)
+                    {
+                        update_card_stmt.execute(params![
+                            list_id,
+                            card.title,
+                            card.description,
+                            card.created_at.timestamp(),
+                            card.updated_at.timestamp(),
+                            card.due.map(|due| due.to_string()),
+                            card.assignee,
+                            card_pos as i64,
+                            card_id,
+                        ])?;
+                    }
+                    card_id
+                }
+                None => {
+                    insert_card_stmt.execute(params![
+                        card.id,
+                        list_id,
+                        card.title,
+                        card.description,
+                        card.created_at.timestamp(),
+                        card.updated_at.timestamp(),
+                        card.due.map(|due| due.to_string()),
+                        card.assignee,
+                        card_pos as i64,
+                    ])?;
+                    let card_id = tx.last_insert_rowid();
+                    card.id = Some(card_id);
+                    card_id
+                }
+            };
+            let stored = stored.as_ref().map(|stored| &stored.card);
+            if stored.map(|stored| &stored.labels) != Some(&card.labels) {
+                delete_labels_stmt.execute([card_id])?;
+                for (label_pos, label) in card.labels.iter().enumerate() {
+                    label_stmt.execute(params![card_id, label, label_pos as i64])?;
+                }
+            }
+            if stored.map(|stored| &stored.checklist) != Some(&card.checklist) {
+                delete_checklist_stmt.execute([card_id])?;
+                for (item_pos, item) in card.checklist.iter().enumerate() {
+                    checklist_stmt.execute(params![
+                        card_id,
+                        item.text,
+                        item.done,
+                        item_pos as i64
+                    ])?;
+                }
+            }
+        }
+    }
+
+    // Whatever is left was removed from the board
+    let mut delete_card_stmt = tx.prepare("DELETE FROM cards WHERE id = ?")?;
+    for id in stored_cards.into_keys() {
+        delete_card_stmt.execute([id])?;
+    }
+    let mut delete_list_stmt = tx.prepare("DELETE FROM lists WHERE id = ?")?;
+    for (id, _) in stored_lists.iter().skip(lists.len()) {
+        delete_list_stmt.execute([id])?;
+    }
+    bump_revision(tx, board_id)
+}
+
+/// A single schema change, applied inside its own transaction.
+type Migration = fn(&Transaction) -> Result<()>;
+
+/// Every schema change in the order it was introduced. The position of a
+/// migration in this slice is its version number minus one, so new
+/// migrations must only ever be appended.
+const MIGRATIONS: &[Migration] = &[
+    migrate_v1_board_schema,
+    migrate_v2_card_details,
+    migrate_v3_checklists,
+    migrate_v4_board_revisions,
+    migrate_v5_board_opened,
+];
+
+/// Brings the database up to the latest schema version. A database written
+/// by a newer version of the app is refused rather than written to.
+fn migrate(conn: &mut Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER NOT NULL)",
+        [],
+    )?;
+    let current: Option<i64> = conn
+        .query_row("SELECT version FROM schema_version", [], |row| row.get(0))
+        .optional()?;
+    if current.is_none() {
+        conn.execute("INSERT INTO schema_version (version) VALUES (0)", [])?;
+    }
+    let current = current.unwrap_or(0) as usize;
+    if current > MIGRATIONS.len() {
+        return Err(rusqlite::Error::SqliteFailure(
+            ffi::Error::new(ffi::SQLITE_ERROR),
+            Some(format!(
+                "the database uses schema version {current}, newer than the {} this version \
+                 of kanban-tui knows",
+                MIGRATIONS.len()
+            )),
+        ));
+    }
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(current) {
+        let tx = conn.transaction()?;
+        migration(&tx)?;
+        tx.execute("UPDATE schema_version SET version = ?", [index as i64 + 1])?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+fn has_column(tx: &Transaction, table: &str, column: &str) -> Result<bool> {
+    let mut stmt = tx.prepare("SELECT name FROM pragma_table_info(?)")?;
+    let columns = stmt
+        .query_map([table], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(columns.iter().any(|name| name == column))
+}
+
+/// Boards, lists and cards with position columns.
+///
+/// Older databases have a flat `cards (id, name)` table. Its card names are
+/// kept as a list named "Imported" on the default board.
+fn migrate_v1_board_schema(tx: &Transaction) -> Result<()> {
+    let legacy_cards = has_column(tx, "cards", "name")? && !has_column(tx, "cards", "list_id")?;
+    if legacy_cards {
+        tx.execute("ALTER TABLE cards RENAME TO legacy_cards", [])?;
+    }
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS boards (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT UNIQUE NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS lists (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            board_id INTEGER NOT NULL REFERENCES boards(id) ON DELETE CASCADE,
+            name TEXT NOT NULL,
+            position INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS cards (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            list_id INTEGER NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+            title TEXT NOT NULL,
+            position INTEGER NOT NULL
+        );",
+    )?;
+    if legacy_cards {
+        tx.execute(
+            "INSERT OR IGNORE INTO boards (name) VALUES (?)",
+            [DEFAULT_BOARD],
+        )?;
+        let board_id: i64 = tx.query_row(
+            "SELECT id FROM boards WHERE name = ?",
+            [DEFAULT_BOARD],
+            |row| row.get(0),
+        )?;
+        tx.execute(
+            "INSERT INTO lists (board_id, name, position)
+             VALUES (?1, 'Imported', (SELECT COUNT(*) FROM lists WHERE board_id = ?1))",
+            [board_id],
+        )?;
+        tx.execute(
+            "INSERT INTO cards (list_id, title, position)
+             SELECT ?, name, ROW_NUMBER() OVER (ORDER BY id) - 1 FROM legacy_cards",
+            [tx.last_insert_rowid()],
+        )?;
+        tx.execute("DROP TABLE legacy_cards", [])?;
+    }
+    Ok(())
+}
+
+/// Card descriptions, timestamps, due dates, assignees and labels.
+fn migrate_v2_card_details(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE cards ADD COLUMN description TEXT NOT NULL DEFAULT '';
+        ALTER TABLE cards ADD COLUMN created_at INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE cards ADD COLUMN updated_at INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE cards ADD COLUMN due TEXT;
+        ALTER TABLE cards ADD COLUMN assignee TEXT;
+        UPDATE cards SET created_at = CAST(strftime('%s', 'now') AS INTEGER),
+            updated_at = CAST(strftime('%s', 'now') AS INTEGER);
+        CREATE TABLE card_labels (
+            card_id INTEGER NOT NULL REFERENCES cards(id) ON DELETE CASCADE,
+            name TEXT NOT NULL,
+            position INTEGER NOT NULL
+        );",
+    )
+}
+
+/// Per-card checklist items.
+fn migrate_v3_checklists(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE checklist_items (
+            card_id INTEGER NOT NULL REFERENCES cards(id) ON DELETE CASCADE,
+            text TEXT NOT NULL,
+            done INTEGER NOT NULL DEFAULT 0,
+            position INTEGER NOT NULL
+        );",
+    )
+}
+
+/// A revision counter per board, see [`read_revision`].
+fn migrate_v4_board_revisions(tx: &Transaction) -> Result<()> {
+    tx.execute(
+        "ALTER TABLE boards ADD COLUMN revision INTEGER NOT NULL DEFAULT 0",
+        [],
+    )?;
+    Ok(())
+}
+
+/// The order boards were opened in, see [`last_opened_board`]. The default
+/// board counts as opened last, as it was the one always opened before.
+fn migrate_v5_board_opened(tx: &Transaction) -> Result<()> {
+    tx.execute(
+        "ALTER TABLE boards ADD COLUMN opened INTEGER NOT NULL DEFAULT 0",
+        [],
+    )?;
+    tx.execute(
+        "UPDATE boards SET opened = 1 WHERE name = ?",
+        [DEFAULT_BOARD],
+    )?;
+    Ok(())
+}
+
+/// Opens the database, applying any pending migrations the first time.
 fn open_db() -> Result<Connection, rusqlite::Error> {
-    Connection::open("example.db")
-    // return conn;
+    let path = db_path();
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        // Let SQLite report the error if the directory still cannot be used.
+        let _ = fs::create_dir_all(parent);
+    }
+    let mut conn = Connection::open(path)?;
+    conn.pragma_update(None, "foreign_keys", true)?;
+    if !MIGRATED.load(Ordering::Relaxed) {
+        migrate(&mut conn)?;
+        MIGRATED.store(true, Ordering::Relaxed);
+    }
+    Ok(conn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrations_refuse_a_newer_schema() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        // Migrating again is a no-op
+        migrate(&mut conn).unwrap();
+
+        conn.execute(
+            "UPDATE schema_version SET version = ?",
+            [MIGRATIONS.len() as i64 + 1],
+        )
+        .unwrap();
+        let error = migrate(&mut conn).unwrap_err().to_string();
+        assert!(error.contains("newer"), "{error}");
+    }
 }
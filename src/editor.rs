@@ -0,0 +1,232 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// A single line of text being edited, with a cursor.
+///
+/// The cursor is a byte offset that always sits on a grapheme cluster
+/// boundary, so every motion and deletion acts on whole user-perceived
+/// characters.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LineEditor {
+    text: String,
+    cursor: usize,
+}
+
+impl LineEditor {
+    /// Starts editing `text` with the cursor at the end.
+    pub fn new(text: impl Into<String>) -> Self {
+        let text = text.into();
+        Self {
+            cursor: text.len(),
+            text,
+        }
+    }
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+    pub fn into_text(self) -> String {
+        self.text
+    }
+
+    fn prev_boundary(&self) -> usize {
+        self.text[..self.cursor]
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(index, _)| index)
+    }
+    fn next_boundary(&self) -> usize {
+        self.text[self.cursor..]
+            .graphemes(true)
+            .next()
+            .map_or(self.cursor, |grapheme| self.cursor + grapheme.len())
+    }
+
+    // Motions
+    pub fn left(&mut self) {
+        self.cursor = self.prev_boundary();
+    }
+    pub fn right(&mut self) {
+        self.cursor = self.next_boundary();
+    }
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+    pub fn end(&mut self) {
+        self.cursor = self.text.len();
+    }
+
+    // Edits
+    pub fn insert(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+    pub fn insert_str(&mut self, s: &str) {
+        self.text.insert_str(self.cursor, s);
+        self.cursor += s.len();
+    }
+    pub fn backspace(&mut self) {
+        let start = self.prev_boundary();
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+    pub fn delete(&mut self) {
+        let end = self.next_boundary();
+        self.text.replace_range(self.cursor..end, "");
+    }
+    /// Deletes the word before the cursor along with any whitespace between
+    /// it and the cursor, like Ctrl-w in a shell.
+    pub fn delete_word(&mut self) {
+        let mut graphemes = self.text[..self.cursor].grapheme_indices(true).rev().peekable();
+        let is_space = |grapheme: &str| grapheme.chars().all(char::is_whitespace);
+        let mut start = self.cursor;
+        while let Some((index, _)) = graphemes.next_if(|(_, g)| is_space(g)) {
+            start = index;
+        }
+        while let Some((index, _)) = graphemes.next_if(|(_, g)| !is_space(g)) {
+            start = index;
+        }
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    /// The part of the text that fits in `width` columns, scrolled so the
+    /// cursor stays visible, and the column of the cursor within it. Line
+    /// breaks are shown as `↵`.
+    pub fn view(&self, width: u16) -> (String, u16) {
+        let width = usize::from(width.max(1));
+        let before = self.text[..self.cursor].graphemes(true);
+        let cursor_col: usize = before.map(shown_width).sum();
+        // Leave a column for the cursor after the last character.
+        let start_col = cursor_col.saturating_sub(width - 1);
+
+        // Where the first shown grapheme starts, past `start_col` if that
+        // falls inside a wide character
+        let mut shown_col = None;
+        let mut col = 0;
+        let mut visible = String::new();
+        for grapheme in self.text.graphemes(true) {
+            let grapheme_width = shown_width(grapheme);
+            if col >= start_col {
+                let shown_col = *shown_col.get_or_insert(col);
+                if col + grapheme_width - shown_col > width {
+                    break;
+                }
+                let shown = if is_line_break(grapheme) {
+                    "↵"
+                } else {
+                    grapheme
+                };
+                visible.push_str(shown);
+            }
+            col += grapheme_width;
+        }
+        let shown_col = shown_col.unwrap_or(start_col).min(cursor_col);
+        (visible, (cursor_col - shown_col) as u16)
+    }
+}
+
+fn is_line_break(grapheme: &str) -> bool {
+    matches!(grapheme, "\n" | "\r\n")
+}
+
+/// The columns `grapheme` takes up in [`LineEditor::view`].
+fn shown_width(grapheme: &str) -> usize {
+    if is_line_break(grapheme) {
+        1
+    } else {
+        grapheme.width()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // "e" followed by a combining acute accent, and a family emoji made of
+    // four people joined by zero width joiners.
+    const E_ACUTE: &str = "e\u{301}";
+    const FAMILY: &str = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}\u{200D}\u{1F466}";
+
+    #[test]
+    fn motions_step_over_whole_graphemes() {
+        let mut editor = LineEditor::new(format!("a{E_ACUTE}{FAMILY}b"));
+        editor.left();
+        assert_eq!(editor.cursor, editor.text.len() - 1);
+        editor.left();
+        assert_eq!(editor.cursor, 1 + E_ACUTE.len());
+        editor.left();
+        assert_eq!(editor.cursor, 1);
+        editor.right();
+        editor.right();
+        assert_eq!(editor.cursor, 1 + E_ACUTE.len() + FAMILY.len());
+        editor.home();
+        editor.left();
+        assert_eq!(editor.cursor, 0);
+        editor.end();
+        editor.right();
+        assert_eq!(editor.cursor, editor.text.len());
+    }
+
+    #[test]
+    fn deletions_remove_whole_graphemes() {
+        let mut editor = LineEditor::new(format!("{E_ACUTE}{FAMILY}"));
+        editor.backspace();
+        assert_eq!(editor.text(), E_ACUTE);
+        editor.insert_str(FAMILY);
+        editor.home();
+        editor.delete();
+        assert_eq!(editor.text(), FAMILY);
+        editor.delete();
+        assert_eq!(editor.text(), "");
+        editor.backspace();
+        editor.delete();
+        assert_eq!((editor.text(), editor.cursor), ("", 0));
+    }
+
+    #[test]
+    fn delete_word_removes_the_word_and_spaces_before_the_cursor() {
+        let mut editor = LineEditor::new(format!("fix caf{E_ACUTE} {FAMILY}  "));
+        editor.delete_word();
+        assert_eq!(editor.text(), format!("fix caf{E_ACUTE} "));
+        editor.delete_word();
+        assert_eq!(editor.text(), "fix ");
+        // Only what is before the cursor goes
+        editor.insert_str("the bug");
+        editor.left();
+        editor.left();
+        editor.left();
+        editor.delete_word();
+        assert_eq!(editor.text(), "fix bug");
+        editor.home();
+        editor.delete_word();
+        assert_eq!(editor.text(), "fix bug");
+    }
+
+    #[test]
+    fn view_scrolls_by_columns_with_wide_characters() {
+        // Each of these takes two columns
+        let mut editor = LineEditor::new("漢字テキスト");
+        assert_eq!(editor.view(20), ("漢字テキスト".into(), 12));
+        // The cursor keeps a column after the text, so two characters go,
+        // rather than showing half of one
+        assert_eq!(editor.view(10), ("テキスト".into(), 8));
+        assert_eq!(editor.view(9), ("テキスト".into(), 8));
+        assert_eq!(editor.view(8), ("キスト".into(), 6));
+        editor.home();
+        assert_eq!(editor.view(5), ("漢字".into(), 0));
+        editor.right();
+        assert_eq!(editor.view(5), ("漢字".into(), 2));
+    }
+
+    #[test]
+    fn view_shows_line_breaks() {
+        let mut editor = LineEditor::new("one\ntwo");
+        assert_eq!(editor.view(20), ("one↵two".into(), 7));
+        editor.home();
+        editor.right();
+        editor.right();
+        editor.right();
+        editor.right();
+        assert_eq!(editor.view(20).1, 4);
+    }
+}
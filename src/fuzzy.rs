@@ -0,0 +1,100 @@
+const MATCH: i64 = 16;
+const CONSECUTIVE: i64 = 16;
+const WORD_START: i64 = 24;
+const GAP: i64 = 1;
+
+/// Fuzzy matching for the go to card palette: `query` matches `text` when
+/// its characters appear in the text in order, ignoring case and whitespace
+/// in the query. Matches score higher when their characters are consecutive
+/// or start words, and lower the more characters they skip.
+///
+/// Returns the score of the best match and the indices of the matched
+/// characters of `text`, or `None` if it does not match.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<(i64, Vec<usize>)> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(to_lower)
+        .collect();
+    let chars: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = chars.iter().map(|&c| to_lower(c)).collect();
+    let (n, m) = (query.len(), chars.len());
+    if n == 0 {
+        return Some((0, Vec::new()));
+    }
+    if n > m {
+        return None;
+    }
+
+    // score[i][j]: the best score with query[..=i] matched and query[i] on
+    // text[j], reached from query[i - 1] on text[from[i][j]]
+    let mut score = vec![vec![None; m]; n];
+    let mut from = vec![vec![0; m]; n];
+    for i in 0..n {
+        // The best previous match before j, with its gap penalty offset by k
+        let mut best: Option<(i64, usize)> = None;
+        for j in 0..m {
+            if i > 0 && j > 0 {
+                if let Some(prev) = score[i - 1][j - 1] {
+                    let offset = prev + GAP * (j - 1) as i64;
+                    if best.is_none_or(|(best, _)| offset > best) {
+                        best = Some((offset, j - 1));
+                    }
+                }
+            }
+            if lower[j] != query[i] {
+                continue;
+            }
+            let word_start = if is_word_start(&chars, j) {
+                WORD_START
+            } else {
+                0
+            };
+            let bonus = MATCH + word_start;
+            if i == 0 {
+                score[i][j] = Some(bonus - GAP * j as i64);
+                continue;
+            }
+            let gapped = best.map(|(offset, k)| (offset - GAP * (j - 1) as i64, k));
+            let consecutive = j
+                .checked_sub(1)
+                .and_then(|k| Some((score[i - 1][k]? + CONSECUTIVE, k)));
+            let found = match (gapped, consecutive) {
+                (Some(gapped), Some(consecutive)) => Some(gapped.max(consecutive)),
+                (found, None) | (None, found) => found,
+            };
+            if let Some((value, k)) = found {
+                score[i][j] = Some(value + bonus);
+                from[i][j] = k;
+            }
+        }
+    }
+
+    let (mut j, total) = (0..m)
+        .filter_map(|j| score[n - 1][j].map(|value| (j, value)))
+        .max_by_key(|&(j, value)| (value, std::cmp::Reverse(j)))?;
+    let mut positions = vec![0; n];
+    for i in (0..n).rev() {
+        positions[i] = j;
+        j = from[i][j];
+    }
+    Some((total, positions))
+}
+
+fn to_lower(c: char) -> char {
+    let mut lower = c.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some(lower), None) => lower,
+        _ => c,
+    }
+}
+
+/// Whether the character at `index` starts a word: it follows a separator or
+/// is an uppercase letter after a lowercase one.
+fn is_word_start(chars: &[char], index: usize) -> bool {
+    let Some(&prev) = index.checked_sub(1).and_then(|i| chars.get(i)) else {
+        return true;
+    };
+    let c = chars[index];
+    !prev.is_alphanumeric() || (prev.is_lowercase() && c.is_uppercase())
+}
@@ -1,63 +1,216 @@
 use crate::app::{App, AppMode, AppResult};
-use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crate::keymap::Action;
+use crate::ui;
+use crossterm::event::{
+    KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
 
 pub fn handle_key_events(ev: KeyEvent, app: &mut App) -> AppResult<()> {
     if ev.kind != KeyEventKind::Press {
         return Ok(());
     }
+    app.clear_message();
+    let typed = !ev.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+    let action = app.keymap().action(app.keymap_mode(), &ev);
+    // Any other key cancels a board deletion waiting for confirmation
+    if action != Some(Action::RemoveBoard) {
+        app.cancel_remove_board();
+    }
+    match (action, ev.code) {
+        (Some(action), _) => perform(action, app)?,
+        // Unbound characters are typed into the text being edited
+        (None, KeyCode::Char(c)) if typed && app.keymap_mode().takes_text() => {
+            app.editor_mut().insert(c);
+        }
+        _ => {}
+    }
+    // The board is filtered as the query is typed
     match app.mode() {
-        AppMode::Main => match ev.code {
-            // Card Movemenent
-            // HJKL: Move card
-            KeyCode::Char('H') | KeyCode::Left if ev.modifiers == KeyModifiers::SHIFT => {
-                app.move_left();
-            }
-            KeyCode::Char('J') | KeyCode::Down if ev.modifiers == KeyModifiers::SHIFT => {
-                app.move_down();
-            }
-            KeyCode::Char('K') | KeyCode::Up if ev.modifiers == KeyModifiers::SHIFT => {
-                app.move_up();
+        AppMode::Search => app.update_search(),
+        AppMode::Palette => app.update_palette(),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Runs `action` in the current mode of `app`.
+fn perform(action: Action, app: &mut App) -> AppResult<()> {
+    if app.help() {
+        match action {
+            Action::MotionDown | Action::ScrollDown => app.help_scroll_down(),
+            Action::MotionUp | Action::ScrollUp => app.help_scroll_up(),
+            Action::Close => app.close_help(),
+            Action::Quit => app.quit(),
+            _ => {}
+        }
+        return Ok(());
+    }
+    let mode = app.mode();
+    match action {
+        // Selection Motions
+        Action::MotionLeft => app.motion_left(),
+        Action::MotionDown => match mode {
+            AppMode::Palette => app.palette_down(),
+            AppMode::BoardPicker => app.board_motion_down(),
+            AppMode::CardDetail => app.check_motion_down(),
+            _ => app.motion_down(),
+        },
+        Action::MotionUp => match mode {
+            AppMode::Palette => app.palette_up(),
+            AppMode::BoardPicker => app.board_motion_up(),
+            AppMode::CardDetail => app.check_motion_up(),
+            _ => app.motion_up(),
+        },
+        Action::MotionRight => app.motion_right(),
+        // Card Movements
+        Action::MoveCardLeft => app.move_left(),
+        Action::MoveCardDown => app.move_down(),
+        Action::MoveCardUp => app.move_up(),
+        Action::MoveCardRight => app.move_right(),
+        // CRUD Operations on Cards
+        Action::AppendCard => app.append_card(),
+        Action::PrependCard => app.prepend_card(),
+        Action::EditCard => app.edit_card(),
+        Action::RemoveCard => app.remove_card(),
+        Action::OpenCard => app.open_card_detail(),
+        // CRUD Operations On Lists
+        Action::AppendList => app.append_list(),
+        Action::PrependList => app.prepend_list(),
+        Action::EditList => app.edit_list(),
+        Action::RemoveList => app.remove_list(),
+        // Undo/Redo
+        Action::Undo => app.undo(),
+        Action::Redo => app.redo(),
+        // Search
+        Action::Search => app.start_search(),
+        Action::SearchNext => app.search_next(),
+        Action::SearchPrev => app.search_prev(),
+        Action::ClearSearch => app.clear_search(),
+        Action::OpenPalette => app.open_palette(),
+        // Command Line
+        Action::Command => app.start_command()?,
+        Action::Complete => {
+            if mode == AppMode::Command {
+                app.complete_command();
             }
-            KeyCode::Char('L') | KeyCode::Right if ev.modifiers == KeyModifiers::SHIFT => {
-                app.move_right()
+        }
+        Action::ToggleRegex => {
+            if mode == AppMode::Search {
+                app.toggle_search_regex();
             }
-            // Selection Motions
-            // hjkl: Move cursor
-            KeyCode::Char('h') | KeyCode::Left => app.motion_left(),
-            KeyCode::Char('j') | KeyCode::Down => app.motion_down(),
-            KeyCode::Char('k') | KeyCode::Up => app.motion_up(),
-            KeyCode::Char('l') | KeyCode::Right => app.motion_right(),
-            // CRUD Operations On Lists
-            // AIEX: Append/Prepend/Edit/Delete a list
-            KeyCode::Char('A') if ev.modifiers == KeyModifiers::SHIFT => app.append_list(),
-            KeyCode::Char('I') if ev.modifiers == KeyModifiers::SHIFT => app.prepend_list(),
-            KeyCode::Char('E') if ev.modifiers == KeyModifiers::SHIFT => app.edit_list(),
-            KeyCode::Char('X') if ev.modifiers == KeyModifiers::SHIFT => app.remove_list(),
-            // CRUD Operations on Cards
-            // aiex: Append/Prepend/Edit/Delete a card
-            KeyCode::Char('a') => app.append_card(),
-            KeyCode::Char('i') => app.prepend_card(),
-            KeyCode::Char('e') => app.edit_card(),
-            KeyCode::Char('x') => app.remove_card(),
-            // Exit
-            KeyCode::Char('q') => app.quit(),
-            KeyCode::Char('c' | 'C') if ev.modifiers == KeyModifiers::CONTROL => app.quit(),
-            _ => {}
+        }
+        // Boards
+        Action::OpenBoards => app.open_board_picker()?,
+        Action::SwitchBoard => app.switch_board()?,
+        Action::AppendBoard => app.append_board()?,
+        Action::EditBoard => app.edit_board(),
+        Action::RemoveBoard => app.remove_board()?,
+        // Checklist
+        Action::ToggleItem => app.toggle_check_item(),
+        Action::MoveItemDown => app.move_check_item_down(),
+        Action::MoveItemUp => app.move_check_item_up(),
+        Action::AppendItem => app.append_check_item(),
+        Action::EditItem => app.edit_check_item(),
+        Action::RemoveItem => app.remove_check_item(),
+        Action::EditDescription => app.edit_description(),
+        Action::ScrollDown => app.detail_scroll_down(),
+        Action::ScrollUp => app.detail_scroll_up(),
+        // Text Editing
+        Action::Confirm => match mode {
+            AppMode::Search => app.done_search(),
+            AppMode::Palette => app.done_palette(),
+            AppMode::Command => app.done_command(),
+            AppMode::ChecklistEdit => app.done_check_edit(),
+            AppMode::DescriptionEdit => app.done_description_edit(),
+            AppMode::BoardEdit => app.done_board_edit()?,
+            _ => app.done_editing(),
         },
-        AppMode::CardEdit => match ev.code {
-            KeyCode::Enter => app.done_editing(),
-            KeyCode::Esc => app.cancel_card_edit(),
-            KeyCode::Backspace => app.backspace_card(),
-            KeyCode::Char(c) => app.type_card(c),
-            _ => {}
+        Action::Cancel => match mode {
+            AppMode::Search => app.cancel_search(),
+            AppMode::Palette => app.cancel_palette(),
+            AppMode::Command => app.cancel_command(),
+            AppMode::ListEdit => app.cancel_list_edit(),
+            AppMode::ChecklistEdit => app.cancel_check_edit(),
+            AppMode::DescriptionEdit => app.cancel_description_edit(),
+            AppMode::BoardEdit => app.cancel_board_edit(),
+            _ => app.cancel_card_edit(),
         },
-        AppMode::ListEdit => match ev.code {
-            KeyCode::Enter => app.done_editing(),
-            KeyCode::Esc => app.cancel_list_edit(),
-            KeyCode::Backspace => app.backspace_list(),
-            KeyCode::Char(c) => app.type_list(c),
-            _ => {}
+        Action::CursorLeft => app.editor_mut().left(),
+        Action::CursorRight => app.editor_mut().right(),
+        Action::CursorHome => app.editor_mut().home(),
+        Action::CursorEnd => app.editor_mut().end(),
+        Action::DeleteBack => app.editor_mut().backspace(),
+        Action::DeleteForward => app.editor_mut().delete(),
+        Action::DeleteWord => app.editor_mut().delete_word(),
+        Action::NewLine => {
+            if mode == AppMode::DescriptionEdit {
+                app.editor_mut().insert('\n');
+            }
+        }
+        // Exit
+        Action::Help => app.open_help(),
+        Action::Close => match mode {
+            AppMode::BoardPicker => app.close_board_picker(),
+            _ => app.close_card_detail(),
         },
-    };
+        Action::Quit => app.quit(),
+    }
+    Ok(())
+}
+
+pub fn handle_paste_events(text: &str, app: &mut App) -> AppResult<()> {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty())
+        .collect();
+    if app.help() {
+        return Ok(());
+    }
+    match app.mode() {
+        // One card per line, the first one going into the card being edited
+        AppMode::Main | AppMode::CardEdit => app.paste_cards(&lines),
+        AppMode::ListEdit | AppMode::BoardEdit | AppMode::ChecklistEdit | AppMode::Command => {
+            app.editor_mut().insert_str(&lines.join(" "));
+        }
+        AppMode::DescriptionEdit => app.editor_mut().insert_str(&lines.join("\n")),
+        AppMode::Search => {
+            app.editor_mut().insert_str(&lines.join(" "));
+            app.update_search();
+        }
+        AppMode::Palette => {
+            app.editor_mut().insert_str(&lines.join(" "));
+            app.update_palette();
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+pub fn handle_mouse_events(ev: MouseEvent, app: &mut App) -> AppResult<()> {
+    if app.mode() != AppMode::Main || app.help() {
+        return Ok(());
+    }
+    match ev.kind {
+        // Click: Select a card, drag: Move it
+        MouseEventKind::Down(MouseButton::Left) => {
+            if let Some((col, row)) = ui::hit_test(app, ev.column, ev.row) {
+                app.select(col, row);
+                if row.is_some() {
+                    app.start_drag();
+                }
+            }
+        }
+        MouseEventKind::Drag(MouseButton::Left) => {
+            if let Some((col, row)) = ui::hit_test(app, ev.column, ev.row) {
+                app.drag_to(col, row);
+            }
+        }
+        MouseEventKind::Up(MouseButton::Left) => app.stop_drag(),
+        // Wheel: Move cursor
+        MouseEventKind::ScrollDown => app.motion_down(),
+        MouseEventKind::ScrollUp => app.motion_up(),
+        _ => {}
+    }
     Ok(())
 }
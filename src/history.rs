@@ -0,0 +1,57 @@
+/// Undo and redo stacks of saved states.
+///
+/// A state is recorded right before it is changed. Undoing hands back the
+/// last recorded state in exchange for the current one, which becomes
+/// available to redo until the next change is recorded.
+#[derive(Debug)]
+pub struct History<T> {
+    undo: Vec<T>,
+    redo: Vec<T>,
+    limit: usize,
+}
+
+impl<T> History<T> {
+    /// Creates an empty history keeping at most `limit` undo steps.
+    pub fn new(limit: usize) -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            limit,
+        }
+    }
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+    /// The most recently recorded state.
+    pub fn last(&self) -> Option<&T> {
+        self.undo.last()
+    }
+    pub fn record(&mut self, state: T) {
+        if self.undo.len() >= self.limit {
+            self.undo.remove(0);
+        }
+        self.undo.push(state);
+        self.redo.clear();
+    }
+    /// Forgets the most recently recorded state without making it redoable.
+    pub fn discard(&mut self) -> Option<T> {
+        self.undo.pop()
+    }
+    pub fn undo(&mut self, current: T) -> Option<T> {
+        let state = self.undo.pop()?;
+        self.redo.push(current);
+        Some(state)
+    }
+    pub fn redo(&mut self, current: T) -> Option<T> {
+        let state = self.redo.pop()?;
+        self.undo.push(current);
+        Some(state)
+    }
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
@@ -0,0 +1,293 @@
+use chrono::{DateTime, NaiveDate, SubsecRound, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::app::{Card, CardList, ChecklistItem};
+
+/// Version of the JSON schema. Bumped whenever a document written by this
+/// version could not be read by an older one.
+pub const VERSION: u32 = 1;
+
+/// A JSON export of one or more boards. Cards keep their id and every other
+/// field; boards and lists are identified by their names and order instead,
+/// so importing an export gives back the same boards under new ids.
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "boards": [
+///     {
+///       "name": "Work",
+///       "lists": [
+///         {
+///           "name": "Todo",
+///           "cards": [
+///             {
+///               "id": 7,
+///               "title": "Release 1.2",
+///               "description": "Tag the commit and upload the binaries.",
+///               "created_at": "2024-05-01T09:30:00Z",
+///               "updated_at": "2024-05-02T16:00:00Z",
+///               "due": "2024-05-10",
+///               "labels": ["release"],
+///               "assignee": "sam",
+///               "checklist": [{ "text": "Changelog", "done": true }]
+///             }
+///           ]
+///         }
+///       ]
+///     }
+///   ]
+/// }
+/// ```
+///
+/// Only `title` is required for a card: timestamps default to the time of
+/// the import, the other fields to empty, and the `id` to a new one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Document {
+    pub version: u32,
+    pub boards: Vec<BoardData>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BoardData {
+    pub name: String,
+    pub lists: Vec<ListData>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ListData {
+    pub name: String,
+    #[serde(default)]
+    pub cards: Vec<CardData>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CardData {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "now")]
+    pub created_at: DateTime<Utc>,
+    #[serde(default = "now")]
+    pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub due: Option<NaiveDate>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub assignee: Option<String>,
+    #[serde(default)]
+    pub checklist: Vec<ChecklistData>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChecklistData {
+    pub text: String,
+    #[serde(default)]
+    pub done: bool,
+}
+
+fn now() -> DateTime<Utc> {
+    Utc::now().trunc_subsecs(0)
+}
+
+/// Just the version, read before the rest so that documents from a newer
+/// version fail with a clear message rather than a field error.
+#[derive(Deserialize)]
+struct Versioned {
+    version: Option<u32>,
+}
+
+impl Document {
+    pub fn new(boards: Vec<BoardData>) -> Self {
+        Self {
+            version: VERSION,
+            boards,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).unwrap_or_default();
+        json.push('\n');
+        json
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let versioned: Versioned = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        match versioned.version {
+            None => {
+                return Err(String::from(
+                    "missing schema version, use --format trello for Trello exports",
+                ))
+            }
+            Some(0) => return Err(String::from("schema versions start at 1, not 0")),
+            Some(version) if version > VERSION => {
+                return Err(format!(
+                    "schema version {version} is newer than the supported version {VERSION}"
+                ))
+            }
+            Some(_) => {}
+        }
+        serde_json::from_str(text).map_err(|e| e.to_string())
+    }
+}
+
+impl BoardData {
+    pub fn new(name: impl Into<String>, lists: &[CardList]) -> Self {
+        Self {
+            name: name.into(),
+            lists: lists.iter().map(ListData::from).collect(),
+        }
+    }
+
+    pub fn into_lists(self) -> Vec<CardList> {
+        self.lists.into_iter().map(CardList::from).collect()
+    }
+}
+
+impl From<&CardList> for ListData {
+    fn from(list: &CardList) -> Self {
+        Self {
+            name: list.name().to_string(),
+            cards: list.cards().iter().map(CardData::from).collect(),
+        }
+    }
+}
+
+impl From<ListData> for CardList {
+    fn from(list: ListData) -> Self {
+        let cards = list.cards.into_iter().map(Card::from).collect();
+        CardList::with_cards(list.name, cards)
+    }
+}
+
+impl From<&Card> for CardData {
+    fn from(card: &Card) -> Self {
+        Self {
+            id: card.id,
+            title: card.title.clone(),
+            description: card.description.clone(),
+            created_at: card.created_at,
+            updated_at: card.updated_at,
+            due: card.due,
+            labels: card.labels.clone(),
+            assignee: card.assignee.clone(),
+            checklist: card
+                .checklist
+                .iter()
+                .map(|item| ChecklistData {
+                    text: item.text.clone(),
+                    done: item.done,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<CardData> for Card {
+    fn from(card: CardData) -> Self {
+        Self {
+            id: card.id,
+            title: card.title,
+            description: card.description,
+            created_at: card.created_at.trunc_subsecs(0),
+            updated_at: card.updated_at.trunc_subsecs(0),
+            due: card.due,
+            labels: card.labels,
+            assignee: card.assignee,
+            checklist: card
+                .checklist
+                .into_iter()
+                .map(|item| ChecklistItem {
+                    text: item.text,
+                    done: item.done,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document() -> Document {
+        let mut card = Card::new("Release 1.2");
+        card.id = Some(7);
+        card.description = String::from("Tag the commit\nand upload.");
+        card.due = NaiveDate::from_ymd_opt(2024, 5, 10);
+        card.labels = vec![String::from("release"), String::from("ops")];
+        card.assignee = Some(String::from("sam"));
+        card.checklist = vec![ChecklistItem {
+            text: String::from("Changelog"),
+            done: true,
+        }];
+        let lists = [
+            CardList::with_cards(String::from("Todo"), vec![card, Card::new("Plain")]),
+            CardList::with_cards(String::from("Done"), Vec::new()),
+        ];
+        Document::new(vec![
+            BoardData::new("Work", &lists),
+            BoardData::new("Home", &[]),
+        ])
+    }
+
+    #[test]
+    fn round_trips_documents() {
+        let document = document();
+        let json = document.to_json();
+        assert_eq!(Document::from_json(&json), Ok(document.clone()));
+
+        // Converting to cards and back loses nothing either
+        let boards = document.boards.iter().map(|board| {
+            let lists = board.clone().into_lists();
+            BoardData::new(&board.name, &lists)
+        });
+        assert_eq!(Document::new(boards.collect()), document);
+    }
+
+    #[test]
+    fn fills_in_missing_card_fields() {
+        let json = r#"{"version": 1, "boards": [{"name": "Work", "lists": [
+            {"name": "Todo", "cards": [{"title": "Short"}]}, {"name": "Done"}
+        ]}]}"#;
+        let document = Document::from_json(json).unwrap();
+        let lists = document.boards[0].clone().into_lists();
+        assert_eq!(lists[0][0].title, "Short");
+        assert_eq!(lists[0][0].id, None);
+        assert!(lists[1].is_empty());
+    }
+
+    #[test]
+    fn rejects_unsupported_versions() {
+        let newer = r#"{"version": 2, "boards": [], "shelves": []}"#;
+        assert_eq!(
+            Document::from_json(newer),
+            Err(String::from(
+                "schema version 2 is newer than the supported version 1"
+            ))
+        );
+        let zero = r#"{"version": 0, "boards": []}"#;
+        assert_eq!(
+            Document::from_json(zero),
+            Err(String::from("schema versions start at 1, not 0"))
+        );
+        let trello = r#"{"name": "Board", "lists": []}"#;
+        assert!(Document::from_json(trello)
+            .unwrap_err()
+            .starts_with("missing schema version"));
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        let json = r#"{"version": 1, "boards": [{"name": "Work", "lists": [], "color": 1}]}"#;
+        assert!(Document::from_json(json).is_err());
+    }
+}
@@ -0,0 +1,624 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+use crate::app::{AppMode, AppResult};
+
+/// Everything a key can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum Action {
+    // Selection Motions
+    MotionLeft,
+    MotionDown,
+    MotionUp,
+    MotionRight,
+    // Card Movements
+    MoveCardLeft,
+    MoveCardDown,
+    MoveCardUp,
+    MoveCardRight,
+    // Cards
+    AppendCard,
+    PrependCard,
+    EditCard,
+    RemoveCard,
+    OpenCard,
+    // Lists
+    AppendList,
+    PrependList,
+    EditList,
+    RemoveList,
+    // History
+    Undo,
+    Redo,
+    // Search
+    Search,
+    SearchNext,
+    SearchPrev,
+    ClearSearch,
+    ToggleRegex,
+    OpenPalette,
+    // Command Line
+    Command,
+    Complete,
+    // Boards
+    OpenBoards,
+    SwitchBoard,
+    AppendBoard,
+    EditBoard,
+    RemoveBoard,
+    // Checklist
+    ToggleItem,
+    MoveItemDown,
+    MoveItemUp,
+    AppendItem,
+    EditItem,
+    RemoveItem,
+    EditDescription,
+    ScrollDown,
+    ScrollUp,
+    // Text Editing
+    Confirm,
+    Cancel,
+    CursorLeft,
+    CursorRight,
+    CursorHome,
+    CursorEnd,
+    DeleteBack,
+    DeleteForward,
+    DeleteWord,
+    NewLine,
+    // General
+    Help,
+    Close,
+    Quit,
+}
+
+impl Action {
+    /// The keymaps this action can be bound in.
+    pub fn modes(self) -> &'static [KeymapMode] {
+        use Action::*;
+        const MAIN: &[KeymapMode] = &[KeymapMode::Main];
+        const BOARD_PICKER: &[KeymapMode] = &[KeymapMode::BoardPicker];
+        const CARD_DETAIL: &[KeymapMode] = &[KeymapMode::CardDetail];
+        const EDIT: &[KeymapMode] = &[KeymapMode::Edit];
+        match self {
+            MotionLeft | MotionRight | MoveCardLeft | MoveCardDown | MoveCardUp | MoveCardRight
+            | AppendCard | PrependCard | EditCard | RemoveCard | OpenCard | AppendList
+            | PrependList | EditList | RemoveList | Undo | Redo | Search | SearchNext
+            | SearchPrev | ClearSearch | OpenPalette | Command | OpenBoards => MAIN,
+            SwitchBoard | AppendBoard | EditBoard | RemoveBoard => BOARD_PICKER,
+            ToggleItem | MoveItemDown | MoveItemUp | AppendItem | EditItem | RemoveItem
+            | EditDescription => CARD_DETAIL,
+            ToggleRegex | Complete | NewLine => EDIT,
+            Confirm | Cancel | CursorLeft | CursorRight | CursorHome | CursorEnd | DeleteBack
+            | DeleteForward | DeleteWord => &[KeymapMode::Edit, KeymapMode::Palette],
+            ScrollDown | ScrollUp => &[KeymapMode::CardDetail, KeymapMode::Help],
+            Help => &[
+                KeymapMode::Main,
+                KeymapMode::BoardPicker,
+                KeymapMode::CardDetail,
+            ],
+            Close => &[
+                KeymapMode::BoardPicker,
+                KeymapMode::CardDetail,
+                KeymapMode::Help,
+            ],
+            MotionDown | MotionUp => &[
+                KeymapMode::Main,
+                KeymapMode::BoardPicker,
+                KeymapMode::CardDetail,
+                KeymapMode::Help,
+                KeymapMode::Palette,
+            ],
+            Quit => &[
+                KeymapMode::Main,
+                KeymapMode::BoardPicker,
+                KeymapMode::CardDetail,
+                KeymapMode::Help,
+            ],
+        }
+    }
+
+    /// The heading this action is listed under in the help overlay.
+    pub fn category(self) -> &'static str {
+        use Action::*;
+        match self {
+            MotionLeft | MotionDown | MotionUp | MotionRight | ScrollDown | ScrollUp => "Motions",
+            MoveCardLeft | MoveCardDown | MoveCardUp | MoveCardRight | AppendCard | PrependCard
+            | EditCard | RemoveCard | OpenCard | EditDescription => "Cards",
+            AppendList | PrependList | EditList | RemoveList => "Lists",
+            Undo | Redo => "History",
+            Command | Complete => "Commands",
+            Search | SearchNext | SearchPrev | ClearSearch | ToggleRegex | OpenPalette => "Search",
+            OpenBoards | SwitchBoard | AppendBoard | EditBoard | RemoveBoard => "Boards",
+            ToggleItem | MoveItemDown | MoveItemUp | AppendItem | EditItem | RemoveItem => {
+                "Checklist"
+            }
+            Confirm | Cancel | CursorLeft | CursorRight | CursorHome | CursorEnd | DeleteBack
+            | DeleteForward | DeleteWord | NewLine => "Editing",
+            Help | Close | Quit => "General",
+        }
+    }
+
+    /// A short description for the footer and the help overlay.
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::MotionLeft => "select left",
+            Action::MotionDown => "select down",
+            Action::MotionUp => "select up",
+            Action::MotionRight => "select right",
+            Action::MoveCardLeft => "move card left",
+            Action::MoveCardDown => "move card down",
+            Action::MoveCardUp => "move card up",
+            Action::MoveCardRight => "move card right",
+            Action::AppendCard => "append card",
+            Action::PrependCard => "prepend card",
+            Action::EditCard => "rename card",
+            Action::RemoveCard => "delete card",
+            Action::OpenCard => "open card",
+            Action::AppendList => "append list",
+            Action::PrependList => "prepend list",
+            Action::EditList => "rename list",
+            Action::RemoveList => "delete list",
+            Action::Undo => "undo",
+            Action::Redo => "redo",
+            Action::Search => "search",
+            Action::SearchNext => "next match",
+            Action::SearchPrev => "previous match",
+            Action::ClearSearch => "clear search",
+            Action::ToggleRegex => "toggle regex",
+            Action::OpenPalette => "go to card",
+            Action::Command => "command",
+            Action::Complete => "complete",
+            Action::OpenBoards => "boards",
+            Action::SwitchBoard => "open board",
+            Action::AppendBoard => "new board",
+            Action::EditBoard => "rename board",
+            Action::RemoveBoard => "delete board",
+            Action::ToggleItem => "toggle item",
+            Action::MoveItemDown => "move item down",
+            Action::MoveItemUp => "move item up",
+            Action::AppendItem => "add item",
+            Action::EditItem => "edit item",
+            Action::RemoveItem => "delete item",
+            Action::EditDescription => "edit description",
+            Action::ScrollDown => "scroll down",
+            Action::ScrollUp => "scroll up",
+            Action::Confirm => "confirm",
+            Action::Cancel => "cancel",
+            Action::CursorLeft => "cursor left",
+            Action::CursorRight => "cursor right",
+            Action::CursorHome => "line start",
+            Action::CursorEnd => "line end",
+            Action::DeleteBack => "delete back",
+            Action::DeleteForward => "delete forward",
+            Action::DeleteWord => "delete word",
+            Action::NewLine => "new line",
+            Action::Help => "help",
+            Action::Close => "close",
+            Action::Quit => "quit",
+        }
+    }
+}
+
+/// The groups of modes that share a keymap. All text edit modes share
+/// [`KeymapMode::Edit`]; keys not bound there are typed into the text.
+/// [`KeymapMode::Help`] is used while the help overlay is open, and
+/// [`KeymapMode::Palette`] while picking a card to go to; keys not bound
+/// there are typed into the query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeymapMode {
+    Main,
+    BoardPicker,
+    CardDetail,
+    Edit,
+    Help,
+    Palette,
+}
+
+impl KeymapMode {
+    pub const ALL: [KeymapMode; 6] = [
+        KeymapMode::Main,
+        KeymapMode::BoardPicker,
+        KeymapMode::CardDetail,
+        KeymapMode::Edit,
+        KeymapMode::Help,
+        KeymapMode::Palette,
+    ];
+
+    /// Whether keys not bound in this keymap are typed as text.
+    pub fn takes_text(self) -> bool {
+        matches!(self, KeymapMode::Edit | KeymapMode::Palette)
+    }
+}
+
+impl From<AppMode> for KeymapMode {
+    fn from(mode: AppMode) -> Self {
+        match mode {
+            AppMode::Main => KeymapMode::Main,
+            AppMode::BoardPicker => KeymapMode::BoardPicker,
+            AppMode::CardDetail => KeymapMode::CardDetail,
+            AppMode::CardEdit
+            | AppMode::ListEdit
+            | AppMode::BoardEdit
+            | AppMode::ChecklistEdit
+            | AppMode::DescriptionEdit
+            | AppMode::Search
+            | AppMode::Command => KeymapMode::Edit,
+            AppMode::Palette => KeymapMode::Palette,
+        }
+    }
+}
+
+/// A key together with its modifiers, written like `h`, `H`, `ctrl-r`,
+/// `shift-left` or `pagedown`.
+///
+/// Shift is part of the character for character keys, so `H` and `shift-h`
+/// are the same chord.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => Self {
+                code: KeyCode::Char(c.to_ascii_uppercase()),
+                modifiers: modifiers - KeyModifiers::SHIFT,
+            },
+            code => Self { code, modifiers },
+        }
+    }
+}
+
+impl From<&KeyEvent> for KeyChord {
+    fn from(ev: &KeyEvent) -> Self {
+        Self::new(ev.code, ev.modifiers)
+    }
+}
+
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("space", KeyCode::Char(' ')),
+    ("tab", KeyCode::Tab),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("left", KeyCode::Left),
+    ("down", KeyCode::Down),
+    ("up", KeyCode::Up),
+    ("right", KeyCode::Right),
+];
+
+impl FromStr for KeyChord {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The key itself may be a dash, as in `-` or `ctrl--`.
+        let (mods, key) = match s.strip_suffix("--") {
+            Some(mods) => (mods, "-"),
+            None if s == "-" => ("", "-"),
+            None => s.rsplit_once('-').unwrap_or(("", s)),
+        };
+        let mut modifiers = KeyModifiers::NONE;
+        for name in mods.split('-').filter(|name| !name.is_empty()) {
+            modifiers |= match name.to_ascii_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier '{name}' in key '{s}'")),
+            };
+        }
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => {
+                let name = key.to_ascii_lowercase();
+                let named = KEY_NAMES.iter().find(|(key_name, _)| *key_name == name);
+                match named {
+                    Some((_, code)) => *code,
+                    None => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                        Some(n) => KeyCode::F(n),
+                        None => return Err(format!("unknown key '{s}'")),
+                    },
+                }
+            }
+        };
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "shift-")?;
+        }
+        match KEY_NAMES.iter().find(|(_, code)| *code == self.code) {
+            Some((name, _)) => write!(f, "{name}"),
+            None => match self.code {
+                KeyCode::Char(c) => write!(f, "{c}"),
+                KeyCode::F(n) => write!(f, "f{n}"),
+                code => write!(f, "{code:?}"),
+            },
+        }
+    }
+}
+
+/// The `[keys]` table of the config file: key chords mapped to actions,
+/// one table per keymap. Entries are added to the default keymap, replacing
+/// the default binding of the same chord.
+///
+/// ```toml
+/// [keys.main]
+/// "ctrl-n" = "MotionDown"
+/// "ctrl-p" = "MotionUp"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeymapConfig {
+    main: BTreeMap<String, Action>,
+    board_picker: BTreeMap<String, Action>,
+    card_detail: BTreeMap<String, Action>,
+    edit: BTreeMap<String, Action>,
+    help: BTreeMap<String, Action>,
+    palette: BTreeMap<String, Action>,
+}
+
+impl KeymapConfig {
+    fn section(&self, mode: KeymapMode) -> &BTreeMap<String, Action> {
+        match mode {
+            KeymapMode::Main => &self.main,
+            KeymapMode::BoardPicker => &self.board_picker,
+            KeymapMode::CardDetail => &self.card_detail,
+            KeymapMode::Edit => &self.edit,
+            KeymapMode::Help => &self.help,
+            KeymapMode::Palette => &self.palette,
+        }
+    }
+}
+
+const DEFAULT_MAIN: &[(&str, Action)] = &[
+    // hjkl: Move cursor
+    ("h", Action::MotionLeft),
+    ("left", Action::MotionLeft),
+    ("j", Action::MotionDown),
+    ("down", Action::MotionDown),
+    ("k", Action::MotionUp),
+    ("up", Action::MotionUp),
+    ("l", Action::MotionRight),
+    ("right", Action::MotionRight),
+    // HJKL: Move card
+    ("H", Action::MoveCardLeft),
+    ("shift-left", Action::MoveCardLeft),
+    ("J", Action::MoveCardDown),
+    ("shift-down", Action::MoveCardDown),
+    ("K", Action::MoveCardUp),
+    ("shift-up", Action::MoveCardUp),
+    ("L", Action::MoveCardRight),
+    ("shift-right", Action::MoveCardRight),
+    // aiex: Append/Prepend/Edit/Delete a card
+    ("a", Action::AppendCard),
+    ("i", Action::PrependCard),
+    ("e", Action::EditCard),
+    ("x", Action::RemoveCard),
+    ("enter", Action::OpenCard),
+    // AIEX: Append/Prepend/Edit/Delete a list
+    ("A", Action::AppendList),
+    ("I", Action::PrependList),
+    ("E", Action::EditList),
+    ("X", Action::RemoveList),
+    ("u", Action::Undo),
+    ("ctrl-r", Action::Redo),
+    // /nN: Search and jump between matches
+    ("/", Action::Search),
+    ("n", Action::SearchNext),
+    ("N", Action::SearchPrev),
+    ("esc", Action::ClearSearch),
+    ("ctrl-p", Action::OpenPalette),
+    (":", Action::Command),
+    ("b", Action::OpenBoards),
+    ("?", Action::Help),
+    ("q", Action::Quit),
+    ("ctrl-c", Action::Quit),
+    ("ctrl-C", Action::Quit),
+];
+
+const DEFAULT_BOARD_PICKER: &[(&str, Action)] = &[
+    ("j", Action::MotionDown),
+    ("down", Action::MotionDown),
+    ("k", Action::MotionUp),
+    ("up", Action::MotionUp),
+    ("enter", Action::SwitchBoard),
+    // aex: Append/Rename/Delete a board
+    ("a", Action::AppendBoard),
+    ("e", Action::EditBoard),
+    ("x", Action::RemoveBoard),
+    ("?", Action::Help),
+    ("esc", Action::Close),
+    ("b", Action::Close),
+    ("q", Action::Close),
+    ("ctrl-c", Action::Quit),
+    ("ctrl-C", Action::Quit),
+];
+
+const DEFAULT_CARD_DETAIL: &[(&str, Action)] = &[
+    // jk: Move cursor, JK: Move item, space: Toggle item
+    ("j", Action::MotionDown),
+    ("down", Action::MotionDown),
+    ("k", Action::MotionUp),
+    ("up", Action::MotionUp),
+    ("J", Action::MoveItemDown),
+    ("shift-down", Action::MoveItemDown),
+    ("K", Action::MoveItemUp),
+    ("shift-up", Action::MoveItemUp),
+    ("space", Action::ToggleItem),
+    // aex: Append/Edit/Delete an item
+    ("a", Action::AppendItem),
+    ("e", Action::EditItem),
+    ("x", Action::RemoveItem),
+    ("d", Action::EditDescription),
+    ("pagedown", Action::ScrollDown),
+    ("pageup", Action::ScrollUp),
+    ("?", Action::Help),
+    ("esc", Action::Close),
+    ("enter", Action::Close),
+    ("q", Action::Close),
+    ("ctrl-c", Action::Quit),
+    ("ctrl-C", Action::Quit),
+];
+
+const DEFAULT_EDIT: &[(&str, Action)] = &[
+    ("enter", Action::Confirm),
+    ("esc", Action::Cancel),
+    ("left", Action::CursorLeft),
+    ("right", Action::CursorRight),
+    ("home", Action::CursorHome),
+    ("end", Action::CursorEnd),
+    ("backspace", Action::DeleteBack),
+    ("delete", Action::DeleteForward),
+    ("ctrl-w", Action::DeleteWord),
+    // Only in descriptions
+    ("alt-enter", Action::NewLine),
+    ("ctrl-j", Action::NewLine),
+    ("ctrl-r", Action::ToggleRegex),
+    ("tab", Action::Complete),
+];
+
+const DEFAULT_PALETTE: &[(&str, Action)] = &[
+    ("enter", Action::Confirm),
+    ("esc", Action::Cancel),
+    ("down", Action::MotionDown),
+    ("ctrl-n", Action::MotionDown),
+    ("up", Action::MotionUp),
+    ("ctrl-p", Action::MotionUp),
+    ("left", Action::CursorLeft),
+    ("right", Action::CursorRight),
+    ("home", Action::CursorHome),
+    ("end", Action::CursorEnd),
+    ("backspace", Action::DeleteBack),
+    ("delete", Action::DeleteForward),
+    ("ctrl-w", Action::DeleteWord),
+];
+
+const DEFAULT_HELP: &[(&str, Action)] = &[
+    ("j", Action::MotionDown),
+    ("down", Action::MotionDown),
+    ("k", Action::MotionUp),
+    ("up", Action::MotionUp),
+    ("pagedown", Action::ScrollDown),
+    ("pageup", Action::ScrollUp),
+    ("?", Action::Close),
+    ("esc", Action::Close),
+    ("q", Action::Close),
+    ("ctrl-c", Action::Quit),
+    ("ctrl-C", Action::Quit),
+];
+
+/// Key chords mapped to actions, for each [`KeymapMode`].
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    main: Vec<(KeyChord, Action)>,
+    board_picker: Vec<(KeyChord, Action)>,
+    card_detail: Vec<(KeyChord, Action)>,
+    edit: Vec<(KeyChord, Action)>,
+    help: Vec<(KeyChord, Action)>,
+    palette: Vec<(KeyChord, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let parse = |bindings: &[(&str, Action)]| {
+            bindings
+                .iter()
+                .map(|(chord, action)| (chord.parse().expect("invalid default key"), *action))
+                .collect()
+        };
+        Self {
+            main: parse(DEFAULT_MAIN),
+            board_picker: parse(DEFAULT_BOARD_PICKER),
+            card_detail: parse(DEFAULT_CARD_DETAIL),
+            edit: parse(DEFAULT_EDIT),
+            help: parse(DEFAULT_HELP),
+            palette: parse(DEFAULT_PALETTE),
+        }
+    }
+}
+
+impl Keymap {
+    /// The default keymap with the bindings from `config` applied on top.
+    pub fn from_config(config: &KeymapConfig) -> AppResult<Self> {
+        let mut keymap = Self::default();
+        for mode in KeymapMode::ALL {
+            for (chord, &action) in config.section(mode) {
+                if !action.modes().contains(&mode) {
+                    return Err(format!("{action:?} cannot be bound in the {mode:?} keymap").into());
+                }
+                keymap.bind(mode, chord.parse()?, action);
+            }
+        }
+        Ok(keymap)
+    }
+
+    fn section_mut(&mut self, mode: KeymapMode) -> &mut Vec<(KeyChord, Action)> {
+        match mode {
+            KeymapMode::Main => &mut self.main,
+            KeymapMode::BoardPicker => &mut self.board_picker,
+            KeymapMode::CardDetail => &mut self.card_detail,
+            KeymapMode::Edit => &mut self.edit,
+            KeymapMode::Help => &mut self.help,
+            KeymapMode::Palette => &mut self.palette,
+        }
+    }
+
+    pub fn bind(&mut self, mode: KeymapMode, chord: KeyChord, action: Action) {
+        let bindings = self.section_mut(mode);
+        match bindings.iter_mut().find(|(bound, _)| *bound == chord) {
+            Some(binding) => binding.1 = action,
+            None => bindings.push((chord, action)),
+        }
+    }
+
+    /// Every binding of a keymap, in the order they were defined.
+    pub fn bindings(&self, mode: KeymapMode) -> &[(KeyChord, Action)] {
+        match mode {
+            KeymapMode::Main => &self.main,
+            KeymapMode::BoardPicker => &self.board_picker,
+            KeymapMode::CardDetail => &self.card_detail,
+            KeymapMode::Edit => &self.edit,
+            KeymapMode::Help => &self.help,
+            KeymapMode::Palette => &self.palette,
+        }
+    }
+
+    /// The chords bound to `action` in a keymap.
+    pub fn chords(&self, mode: KeymapMode, action: Action) -> impl Iterator<Item = KeyChord> + '_ {
+        self.bindings(mode)
+            .iter()
+            .filter(move |(_, bound)| *bound == action)
+            .map(|(chord, _)| *chord)
+    }
+
+    /// The action bound to a key press in the given keymap.
+    pub fn action(&self, mode: KeymapMode, ev: &KeyEvent) -> Option<Action> {
+        let chord = KeyChord::from(ev);
+        self.bindings(mode)
+            .iter()
+            .find(|(bound, _)| *bound == chord)
+            .map(|(_, action)| *action)
+    }
+}
@@ -1,6 +1,19 @@
 pub mod app;
+pub mod cli;
+pub mod command;
+pub mod config;
+pub mod editor;
 pub mod event;
+pub mod fuzzy;
 pub mod handler;
+pub mod history;
+pub mod json;
+pub mod keymap;
+pub mod markdown;
+pub mod search;
+pub mod theme;
+pub mod todotxt;
+pub mod trello;
 pub mod tui;
 pub mod ui;
 pub mod db;
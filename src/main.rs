@@ -1,31 +1,87 @@
 use kanban_tui::app::{App, AppResult};
+use kanban_tui::cli::{Args, USAGE};
+use kanban_tui::config::Config;
+use kanban_tui::db;
 use kanban_tui::event::{Event, EventHandler};
-use kanban_tui::handler::handle_key_events;
+use kanban_tui::handler::{handle_key_events, handle_mouse_events, handle_paste_events};
+use kanban_tui::keymap::Keymap;
+use kanban_tui::theme::Theme;
 use kanban_tui::tui::Tui;
 use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
-use std::io;
-// use kanban_tui::db::{insert_user_data, read_user_data};
+use std::{io, process};
 
 fn main() -> AppResult<()> {
-    let mut app = App::new();
+    let args = match Args::parse() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("error: {e}");
+            process::exit(2);
+        }
+    };
+    if args.help {
+        println!("{USAGE}");
+        return Ok(());
+    }
+    db::set_db_path(args.db_path());
+    if let Some(command) = args.command {
+        if let Err(e) = command.run(&mut io::stdout().lock()) {
+            eprintln!("error: {e}");
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    let config = Config::load(&args.config_path())?;
+    let keymap = Keymap::from_config(&config.keys)?;
+    let theme = Theme::from_config(&config)?;
+    let mut app = App::load_last()?;
+    app.set_keymap(keymap);
+    app.set_theme(theme);
 
     let backend = CrosstermBackend::new(io::stderr());
     let terminal = Terminal::new(backend)?;
     let events = EventHandler::new(250);
     let mut tui = Tui::new(terminal, events);
     tui.init()?;
+    let size = tui.size()?;
+    app.resize(size.width, size.height);
+
+    let result = run(&mut app, &mut tui);
+    // Leave the terminal usable whatever happened
+    tui.exit()?;
+    result
+}
 
+/// Runs the interface until the user quits. Errors from handling an event,
+/// such as the database being busy, are shown in the footer instead of
+/// ending the app.
+fn run(app: &mut App, tui: &mut Tui<CrosstermBackend<io::Stderr>>) -> AppResult<()> {
+    // Ticks only change the board when it was reloaded, so otherwise only
+    // redraw after other events.
+    let mut redraw = true;
     while app.running() {
-        tui.draw(&app)?;
-        match tui.events.next()? {
-            Event::Tick => app.tick(),
-            Event::Key(key_event) => handle_key_events(key_event, &mut app)?,
-            Event::Mouse(_) => {}
-            Event::Resize(_, _) => {}
+        if redraw {
+            tui.draw(app)?;
+        }
+        redraw = true;
+        let handled = match tui.events.next()? {
+            Event::Tick => app.tick().map(|reloaded| redraw = reloaded),
+            Event::Key(key_event) => handle_key_events(key_event, app),
+            Event::Mouse(mouse_event) => handle_mouse_events(mouse_event, app),
+            Event::Resize(width, height) => {
+                app.resize(width, height);
+                Ok(())
+            }
+            Event::Paste(text) => handle_paste_events(&text, app),
+        };
+        let saved = match handled {
+            Ok(()) if !app.mode().is_editing() => app.save(),
+            handled => handled,
+        };
+        if let Err(e) = saved {
+            app.set_message(e.to_string());
+            redraw = true;
         }
     }
-
-    tui.exit()?;
     Ok(())
 }
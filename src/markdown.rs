@@ -0,0 +1,282 @@
+use std::{fmt::Write, fs, path::Path};
+
+use crate::app::{AppResult, Card, CardList, ChecklistItem};
+
+/// Renders a board as Markdown: the board name as the title, each list as a
+/// `##` heading and each card as a `- [ ]` item, with its description quoted
+/// and its checklist indented under it.
+///
+/// ```markdown
+/// # Work
+///
+/// ## Todo
+///
+/// - [ ] Release 1.2
+///   > Tag the commit and upload the binaries.
+///   - [x] Changelog
+///   - [ ] Tag
+/// ```
+///
+/// Every line of the description is quoted, blank ones too, so that it reads
+/// back exactly, even lines that look like checklist items.
+pub fn to_markdown(board: &str, lists: &[CardList]) -> String {
+    let mut out = format!("# {board}\n");
+    for list in lists {
+        let _ = write!(out, "\n## {}\n\n", list.name());
+        for card in list.cards() {
+            let _ = writeln!(out, "- [ ] {}", card.title);
+            if !card.description.is_empty() {
+                for line in card.description.split('\n') {
+                    match line {
+                        "" => out.push_str("  >\n"),
+                        line => {
+                            let _ = writeln!(out, "  > {line}");
+                        }
+                    }
+                }
+            }
+            for item in &card.checklist {
+                let mark = if item.done { 'x' } else { ' ' };
+                let _ = writeln!(out, "  - [{mark}] {}", item.text);
+            }
+        }
+    }
+    out
+}
+
+/// A card being read, with its description lines and whether each was
+/// quoted.
+type Draft<'a> = (Card, Vec<(bool, &'a str)>);
+
+/// Parses Markdown written by [`to_markdown`] back into the board name, if
+/// the text has a `#` title, and its lists.
+///
+/// Cards may also be written as plain `-` or `*` items, and descriptions as
+/// indented text without `>`, whose blank lines only separate paragraphs. Any
+/// other text outside a card is an error, so that nothing is dropped
+/// silently.
+pub fn from_markdown(text: &str) -> Result<(Option<String>, Vec<CardList>), String> {
+    let mut name = None;
+    let mut lists: Vec<(String, Vec<Draft>)> = Vec::new();
+    for (index, raw) in text.lines().enumerate() {
+        let error = |message: &str| format!("line {}: {message}", index + 1);
+        let line = raw.trim_end();
+        if let Some(nested) = line.strip_prefix("  ").or_else(|| line.strip_prefix('\t')) {
+            let (card, description) = lists
+                .last_mut()
+                .and_then(|(_, cards)| cards.last_mut())
+                .ok_or_else(|| error("indented text outside a card"))?;
+            if let Some(quoted) = quote(raw) {
+                description.push((true, quoted));
+            } else if let Some((done, text)) = task(nested) {
+                card.checklist.push(ChecklistItem {
+                    text: text.to_string(),
+                    done,
+                });
+            } else {
+                description.push((false, nested));
+            }
+        } else if line.is_empty() {
+            if let Some((_, description)) = lists.last_mut().and_then(|(_, cards)| cards.last_mut())
+            {
+                description.push((false, ""));
+            }
+        } else if let Some(heading) = line.strip_prefix("## ") {
+            lists.push((heading.trim().to_string(), Vec::new()));
+        } else if let Some(title) = line.strip_prefix("# ") {
+            if name.is_some() || !lists.is_empty() {
+                return Err(error("the board title must come first"));
+            }
+            name = Some(title.trim().to_string());
+        } else if let Some(title) = task(line).map(|(_, title)| title).or_else(|| item(line)) {
+            let (_, cards) = lists
+                .last_mut()
+                .ok_or_else(|| error("card before the first ## list heading"))?;
+            cards.push((Card::new(title), Vec::new()));
+        } else {
+            return Err(error("expected a ## list heading or a - [ ] card"));
+        }
+    }
+    if lists.is_empty() {
+        return Err(String::from("no ## list headings found"));
+    }
+    let lists = lists
+        .into_iter()
+        .map(|(name, cards)| {
+            let cards = cards
+                .into_iter()
+                .map(|(mut card, lines)| {
+                    card.description = description(&lines);
+                    card
+                })
+                .collect();
+            CardList::with_cards(name, cards)
+        })
+        .collect();
+    Ok((name, lists))
+}
+
+/// Joins the description lines of a card. Quoted lines are kept as they
+/// are; without any, blank lines at either end are dropped.
+fn description(lines: &[(bool, &str)]) -> String {
+    let quoted: Vec<&str> = lines
+        .iter()
+        .filter(|(quoted, _)| *quoted)
+        .map(|(_, line)| *line)
+        .collect();
+    if !quoted.is_empty() {
+        return quoted.join("\n");
+    }
+    let lines: Vec<&str> = lines.iter().map(|(_, line)| *line).collect();
+    lines.join("\n").trim_matches('\n').to_string()
+}
+
+/// An indented `>` line, with its trailing whitespace.
+fn quote(line: &str) -> Option<&str> {
+    let nested = line
+        .strip_prefix("  ")
+        .or_else(|| line.strip_prefix('\t'))?;
+    let quoted = nested.strip_prefix('>')?;
+    Some(quoted.strip_prefix(' ').unwrap_or(quoted))
+}
+
+/// A `- [ ]` or `- [x]` item: whether it is done and its text.
+fn task(line: &str) -> Option<(bool, &str)> {
+    let rest = line.strip_prefix("- [")?;
+    let (done, rest) = match rest.get(..2)? {
+        " ]" => (false, &rest[2..]),
+        "x]" | "X]" => (true, &rest[2..]),
+        _ => return None,
+    };
+    match rest.strip_prefix(' ') {
+        Some(text) => Some((done, text)),
+        None if rest.is_empty() => Some((done, rest)),
+        None => None,
+    }
+}
+
+/// A plain `-` or `*` item.
+fn item(line: &str) -> Option<&str> {
+    line.strip_prefix("- ").or_else(|| line.strip_prefix("* "))
+}
+
+/// Writes a board to `path` as Markdown, see [`to_markdown`].
+pub fn export(path: &Path, board: &str, lists: &[CardList]) -> AppResult<()> {
+    fs::write(path, to_markdown(board, lists)).map_err(|e| format!("{}: {e}", path.display()))?;
+    Ok(())
+}
+
+/// Reads the board name and lists from a Markdown file at `path`, see
+/// [`from_markdown`].
+pub fn import(path: &Path) -> AppResult<(Option<String>, Vec<CardList>)> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let board = from_markdown(&text).map_err(|e| format!("{}: {e}", path.display()))?;
+    Ok(board)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(title: &str, description: &str, checklist: &[(&str, bool)]) -> Card {
+        let mut card = Card::new(title);
+        card.description = description.to_string();
+        card.checklist = checklist
+            .iter()
+            .map(|&(text, done)| ChecklistItem {
+                text: text.to_string(),
+                done,
+            })
+            .collect();
+        card
+    }
+
+    /// The title, description and checklist of a card, which is what the
+    /// format keeps.
+    type Kept = (String, String, Vec<ChecklistItem>);
+
+    fn summary(lists: &[CardList]) -> Vec<(String, Vec<Kept>)> {
+        lists
+            .iter()
+            .map(|list| {
+                let cards = list.cards().iter().map(|card| {
+                    (
+                        card.title.clone(),
+                        card.description.clone(),
+                        card.checklist.clone(),
+                    )
+                });
+                (list.name().to_string(), cards.collect())
+            })
+            .collect()
+    }
+
+    fn round_trip(lists: &[CardList]) {
+        let text = to_markdown("Work", lists);
+        let (name, parsed) = from_markdown(&text).unwrap();
+        assert_eq!(name.as_deref(), Some("Work"));
+        assert_eq!(summary(&parsed), summary(lists), "{text}");
+    }
+
+    #[test]
+    fn round_trips_cards_and_checklists() {
+        let lists = [
+            CardList::with_cards(
+                String::from("Todo"),
+                vec![
+                    card(
+                        "Release 1.2",
+                        "Tag the commit.\n\nThen upload.",
+                        &[("Changelog", true), ("Tag", false)],
+                    ),
+                    card("Plain", "", &[]),
+                ],
+            ),
+            CardList::with_cards(String::from("Empty"), Vec::new()),
+            CardList::with_cards(String::from("Done"), vec![card("Old", "", &[("x", true)])]),
+        ];
+        round_trip(&lists);
+    }
+
+    #[test]
+    fn round_trips_descriptions_exactly() {
+        let descriptions = [
+            "- [ ] not a checklist",
+            "- [x] done\n  - nested",
+            "\n\nleading blank lines",
+            "trailing spaces  \nand a blank line\n",
+            "> already quoted",
+            " ",
+        ];
+        let cards = descriptions
+            .iter()
+            .map(|description| card("Card", description, &[("item", false)]))
+            .collect();
+        round_trip(&[CardList::with_cards(String::from("Todo"), cards)]);
+    }
+
+    #[test]
+    fn reads_unquoted_descriptions() {
+        let text = "## Todo\n\n- Card\n\n  First paragraph.\n\n  Second one.\n  - [ ] item\n\n";
+        let (name, lists) = from_markdown(text).unwrap();
+        assert_eq!(name, None);
+        let card = &lists[0][0];
+        assert_eq!(card.title, "Card");
+        assert_eq!(card.description, "First paragraph.\n\nSecond one.");
+        assert_eq!(card.checklist, [ChecklistItem::new("item")]);
+    }
+
+    #[test]
+    fn reports_the_line_of_stray_text() {
+        assert_eq!(
+            from_markdown("## Todo\n\nsome text\n"),
+            Err(String::from(
+                "line 3: expected a ## list heading or a - [ ] card"
+            ))
+        );
+        assert_eq!(
+            from_markdown("  > orphan\n"),
+            Err(String::from("line 1: indented text outside a card"))
+        );
+    }
+}
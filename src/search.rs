@@ -0,0 +1,88 @@
+use std::ops::Range;
+
+use regex::{Regex, RegexBuilder};
+
+use crate::app::Card;
+
+/// The `/` search that filters the cards on the board.
+///
+/// A plain query matches case-insensitive substrings; in regex mode the
+/// query is a regular expression as understood by the `regex` crate.
+#[derive(Debug, Clone, Default)]
+pub struct Search {
+    query: String,
+    regex: bool,
+    pattern: Option<Regex>,
+    error: Option<String>,
+}
+
+impl Search {
+    pub fn new(query: impl Into<String>, regex: bool) -> Self {
+        let query = query.into();
+        let built = if query.is_empty() {
+            None
+        } else if regex {
+            Some(Regex::new(&query))
+        } else {
+            Some(
+                RegexBuilder::new(&regex::escape(&query))
+                    .case_insensitive(true)
+                    .build(),
+            )
+        };
+        let (pattern, error) = match built {
+            Some(Ok(pattern)) => (Some(pattern), None),
+            // The last line of the message says what is wrong
+            Some(Err(e)) => {
+                let message = e.to_string();
+                let reason = message.lines().last().unwrap_or_default().trim();
+                (None, Some(reason.to_string()))
+            }
+            None => (None, None),
+        };
+        Self {
+            query,
+            regex,
+            pattern,
+            error,
+        }
+    }
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+    pub fn regex(&self) -> bool {
+        self.regex
+    }
+    /// Why the query is not a valid regex, if it is not.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+    /// Whether the board is being filtered. An empty or invalid query shows
+    /// every card.
+    pub fn is_active(&self) -> bool {
+        self.pattern.is_some()
+    }
+    /// Whether `card` is shown: its title, labels, assignee, description or
+    /// checklist match the query.
+    pub fn matches(&self, card: &Card) -> bool {
+        let Some(pattern) = &self.pattern else {
+            return true;
+        };
+        pattern.is_match(&card.title)
+            || card.labels.iter().any(|label| pattern.is_match(label))
+            || card.assignee.as_ref().is_some_and(|a| pattern.is_match(a))
+            || pattern.is_match(&card.description)
+            || card.checklist.iter().any(|item| pattern.is_match(&item.text))
+    }
+    /// Byte ranges of the matches in `text`, for highlighting.
+    pub fn find(&self, text: &str) -> Vec<Range<usize>> {
+        match &self.pattern {
+            Some(pattern) => pattern
+                .find_iter(text)
+                .map(|found| found.range())
+                .filter(|range| !range.is_empty())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
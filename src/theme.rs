@@ -0,0 +1,197 @@
+use std::{env, str::FromStr};
+
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+
+use crate::{app::AppResult, config::Config};
+
+/// The theme used when neither the config nor `NO_COLOR` picks one.
+pub const DEFAULT_THEME: &str = "dark";
+
+/// Names of the built-in themes.
+pub const BUILTIN_THEMES: [&str; 4] = ["dark", "light", "high-contrast", "no-color"];
+
+/// The styles the interface is drawn with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// Cards, list borders and other text.
+    pub text: Style,
+    /// The selected card, board or checklist item.
+    pub selected: Style,
+    /// The focused list and the borders of popups.
+    pub focused: Style,
+    /// Secondary details such as labels and due dates.
+    pub muted: Style,
+    /// Search matches.
+    pub highlight: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            text: Style::new().fg(Color::White),
+            selected: Style::new().fg(Color::Green),
+            focused: Style::new().fg(Color::LightBlue),
+            muted: Style::new().add_modifier(Modifier::DIM),
+            highlight: Style::new().fg(Color::Black).bg(Color::Yellow),
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            text: Style::new().fg(Color::Black),
+            selected: Style::new().fg(Color::Blue).add_modifier(Modifier::BOLD),
+            focused: Style::new().fg(Color::Magenta),
+            muted: Style::new().fg(Color::DarkGray),
+            highlight: Style::new().bg(Color::LightYellow),
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Self {
+            text: Style::new().fg(Color::White),
+            selected: Style::new()
+                .fg(Color::Black)
+                .bg(Color::LightYellow)
+                .add_modifier(Modifier::BOLD),
+            focused: Style::new()
+                .fg(Color::LightYellow)
+                .add_modifier(Modifier::BOLD),
+            muted: Style::new().fg(Color::Gray),
+            highlight: Style::new()
+                .fg(Color::Black)
+                .bg(Color::LightCyan)
+                .add_modifier(Modifier::BOLD),
+        }
+    }
+
+    /// Attributes only, for terminals without colors or with `NO_COLOR` set.
+    pub fn no_color() -> Self {
+        Self {
+            text: Style::new(),
+            selected: Style::new().add_modifier(Modifier::REVERSED),
+            focused: Style::new().add_modifier(Modifier::BOLD),
+            muted: Style::new().add_modifier(Modifier::DIM),
+            highlight: Style::new().add_modifier(Modifier::UNDERLINED | Modifier::BOLD),
+        }
+    }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            "no-color" => Some(Self::no_color()),
+            _ => None,
+        }
+    }
+
+    /// The theme named in `config`, which may be built in or defined in its
+    /// `[themes]` table. Without a name, `NO_COLOR` selects `no-color`.
+    /// See <https://no-color.org>.
+    pub fn from_config(config: &Config) -> AppResult<Self> {
+        let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        let name = match &config.theme {
+            Some(name) => name.as_str(),
+            None if no_color => "no-color",
+            None => DEFAULT_THEME,
+        };
+        match config.themes.get(name) {
+            Some(theme) => theme.build(),
+            None => Self::builtin(name).ok_or_else(|| {
+                format!(
+                    "unknown theme '{name}', expected one of {} or a [themes.{name}] table",
+                    BUILTIN_THEMES.join(", ")
+                )
+                .into()
+            }),
+        }
+    }
+}
+
+/// A user theme from the `[themes.<name>]` table of the config file. Each
+/// style is written like `green`, `bold`, `#ffaf00 on black` or
+/// `reversed dim`, and replaces that style of the `base` built-in theme.
+///
+/// ```toml
+/// [themes.solarized]
+/// base = "dark"
+/// text = "#839496"
+/// selected = "bold #b58900"
+/// focused = "#268bd2"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    base: Option<String>,
+    text: Option<String>,
+    selected: Option<String>,
+    focused: Option<String>,
+    muted: Option<String>,
+    highlight: Option<String>,
+}
+
+impl ThemeConfig {
+    fn build(&self) -> AppResult<Theme> {
+        let base = self.base.as_deref().unwrap_or(DEFAULT_THEME);
+        let mut theme = Theme::builtin(base).ok_or_else(|| {
+            format!(
+                "unknown base theme '{base}', expected one of {}",
+                BUILTIN_THEMES.join(", ")
+            )
+        })?;
+        let styles = [
+            (&self.text, &mut theme.text),
+            (&self.selected, &mut theme.selected),
+            (&self.focused, &mut theme.focused),
+            (&self.muted, &mut theme.muted),
+            (&self.highlight, &mut theme.highlight),
+        ];
+        for (spec, style) in styles {
+            if let Some(spec) = spec {
+                *style = parse_style(spec)?;
+            }
+        }
+        Ok(theme)
+    }
+}
+
+/// Parses a style made of modifiers, a foreground color and `on` followed
+/// by a background color, in any order.
+fn parse_style(spec: &str) -> Result<Style, String> {
+    let mut style = Style::new();
+    let mut words = spec.split_whitespace();
+    while let Some(word) = words.next() {
+        let modifier = match word.to_ascii_lowercase().as_str() {
+            "bold" => Modifier::BOLD,
+            "dim" => Modifier::DIM,
+            "italic" => Modifier::ITALIC,
+            "underlined" => Modifier::UNDERLINED,
+            "reversed" => Modifier::REVERSED,
+            "crossed-out" => Modifier::CROSSED_OUT,
+            "on" => {
+                let color = words
+                    .next()
+                    .ok_or_else(|| format!("missing background color in style '{spec}'"))?;
+                style = style.bg(parse_color(color, spec)?);
+                continue;
+            }
+            _ => {
+                style = style.fg(parse_color(word, spec)?);
+                continue;
+            }
+        };
+        style = style.add_modifier(modifier);
+    }
+    Ok(style)
+}
+
+fn parse_color(color: &str, spec: &str) -> Result<Color, String> {
+    Color::from_str(color).map_err(|_| format!("unknown color '{color}' in style '{spec}'"))
+}
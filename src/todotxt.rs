@@ -0,0 +1,218 @@
+use std::fmt::Write;
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::app::{Card, CardList};
+
+/// The list open tasks go to unless they have a `list:` key.
+pub const TODO_LIST: &str = "Todo";
+
+/// The list completed tasks go to.
+pub const DONE_LIST: &str = "Done";
+
+/// Reads tasks in the todo.txt format, see <http://todotxt.org>, into a
+/// `Todo` list, the lists named by `list:` keys and a `Done` list for the
+/// completed `x` tasks.
+///
+/// A `+project` becomes a label, as does an `@context`, which keeps its
+/// `@`; both also stay where they are in the title. The priority becomes a
+/// `pri:A` label, as todo.txt itself writes it for completed tasks, and
+/// `due:` sets the due date. Creation and completion dates set when the
+/// card was created and last updated.
+pub fn from_todotxt(text: &str) -> Vec<CardList> {
+    let mut lists = vec![(TODO_LIST.to_string(), Vec::new())];
+    let mut done = Vec::new();
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let (list, card) = parse_task(line);
+        match list {
+            Task::Done => done.push(card),
+            Task::Open(name) => match lists.iter_mut().find(|(list, _)| *list == name) {
+                Some((_, cards)) => cards.push(card),
+                None => lists.push((name, vec![card])),
+            },
+        }
+    }
+    lists.push((DONE_LIST.to_string(), done));
+    lists
+        .into_iter()
+        .map(|(name, cards)| CardList::with_cards(name, cards))
+        .collect()
+}
+
+/// Where a task goes: the list named by its `list:` key or `Todo`, or
+/// `Done` once completed.
+enum Task {
+    Open(String),
+    Done,
+}
+
+fn parse_task(line: &str) -> (Task, Card) {
+    let mut words = line.split_whitespace().peekable();
+    let mut labels = Vec::new();
+    let is_date = |word: &&str| parse_date(word).is_some();
+    let done = words.next_if_eq(&"x").is_some();
+    // A completed task starts with its completion date, then its creation
+    // date; an open one with its priority, then its creation date.
+    let completed = if done { words.next_if(is_date) } else { None };
+    let priority = words.next_if(|word| !done && parse_priority(word).is_some());
+    if let Some(priority) = priority.and_then(parse_priority) {
+        labels.push(format!("pri:{priority}"));
+    }
+    let created = words.next_if(is_date).or(completed);
+
+    let mut list = TODO_LIST.to_string();
+    let mut due = None;
+    let mut title = Vec::new();
+    for word in words {
+        if word.len() > 1 && (word.starts_with('+') || word.starts_with('@')) {
+            let tag = word.strip_prefix('+').unwrap_or(word);
+            labels.push(tag.to_string());
+            title.push(word);
+        } else if let Some(date) = word.strip_prefix("due:").and_then(parse_date) {
+            due = Some(date);
+        } else if let Some(name) = word.strip_prefix("list:").filter(|n| !n.is_empty()) {
+            list = name.replace('_', " ");
+        } else if word.strip_prefix("pri:").is_some_and(is_priority) {
+            labels.push(word.to_string());
+        } else {
+            title.push(word);
+        }
+    }
+    let mut card = Card::new(title.join(" "));
+    card.labels = labels;
+    card.due = due;
+    if let Some(created) = created.and_then(parse_date) {
+        card.created_at = midnight(created);
+        card.updated_at = card.created_at;
+    }
+    if let Some(completed) = completed.and_then(parse_date) {
+        card.updated_at = midnight(completed);
+    }
+    let task = if done { Task::Done } else { Task::Open(list) };
+    (task, card)
+}
+
+/// Writes every card as a todo.txt task, the reverse of [`from_todotxt`].
+/// Cards in a list named `Done` are written as completed tasks, those of
+/// lists other than `Todo` get a `list:` key. Labels are added as tags after
+/// the title unless it already has them. Descriptions, checklists and
+/// assignees have no place in the format and are left out.
+pub fn to_todotxt(lists: &[CardList]) -> String {
+    let mut out = String::new();
+    for list in lists {
+        let done = list.name().eq_ignore_ascii_case(DONE_LIST);
+        let todo = list.name().eq_ignore_ascii_case(TODO_LIST);
+        for card in list.cards() {
+            let priority = card
+                .labels
+                .iter()
+                .find_map(|label| label.strip_prefix("pri:").filter(|p| is_priority(p)));
+            let created = card.created_at.date_naive();
+            if done {
+                let _ = write!(out, "x {} {created}", card.updated_at.date_naive());
+            } else if let Some(priority) = priority {
+                let _ = write!(out, "({priority}) {created}");
+            } else {
+                let _ = write!(out, "{created}");
+            }
+            let _ = write!(out, " {}", card.title);
+            for label in &card.labels {
+                let tag = label.replace(char::is_whitespace, "_");
+                let tag = if label.starts_with('@') || label.starts_with("pri:") {
+                    tag
+                } else {
+                    format!("+{tag}")
+                };
+                let in_title = card.title.split_whitespace().any(|word| word == tag);
+                if !in_title && (done || !label.starts_with("pri:")) {
+                    let _ = write!(out, " {tag}");
+                }
+            }
+            if let Some(due) = card.due {
+                let _ = write!(out, " due:{due}");
+            }
+            if !done && !todo {
+                let _ = write!(out, " list:{}", list.name().replace(' ', "_"));
+            }
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn parse_date(word: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(word, "%Y-%m-%d").ok()
+}
+
+/// A priority is written `(A)` to `(Z)`.
+fn parse_priority(word: &str) -> Option<&str> {
+    let priority = word.strip_prefix('(')?.strip_suffix(')')?;
+    is_priority(priority).then_some(priority)
+}
+
+fn is_priority(priority: &str) -> bool {
+    priority.len() == 1 && priority.chars().all(|c| c.is_ascii_uppercase())
+}
+
+fn midnight(date: NaiveDate) -> DateTime<Utc> {
+    date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TASKS: &str = "\
+(A) 2024-01-02 Call +mom about taxes @phone due:2024-02-01
+2024-01-03 Plan the trip list:Next_week
+x 2024-01-05 2024-01-04 Pay rent +home pri:B
+";
+
+    #[test]
+    fn reads_tasks_into_lists() {
+        let lists = from_todotxt(TASKS);
+        let names: Vec<&str> = lists.iter().map(CardList::name).collect();
+        assert_eq!(names, ["Todo", "Next week", "Done"]);
+
+        let call = &lists[0][0];
+        assert_eq!(call.title, "Call +mom about taxes @phone");
+        assert_eq!(call.labels, ["pri:A", "mom", "@phone"]);
+        assert_eq!(call.due, NaiveDate::from_ymd_opt(2024, 2, 1));
+        assert_eq!(call.created_at.to_rfc3339(), "2024-01-02T00:00:00+00:00");
+
+        assert_eq!(lists[1][0].title, "Plan the trip");
+
+        let rent = &lists[2][0];
+        assert_eq!(rent.title, "Pay rent +home");
+        assert_eq!(rent.labels, ["home", "pri:B"]);
+        assert_eq!(rent.created_at.to_rfc3339(), "2024-01-04T00:00:00+00:00");
+        assert_eq!(rent.updated_at.to_rfc3339(), "2024-01-05T00:00:00+00:00");
+    }
+
+    #[test]
+    fn round_trips_tasks() {
+        assert_eq!(to_todotxt(&from_todotxt(TASKS)), TASKS);
+    }
+
+    #[test]
+    fn writes_labels_missing_from_the_title() {
+        let mut card = Card::new("Fix the sink");
+        card.created_at = midnight(NaiveDate::from_ymd_opt(2024, 3, 1).unwrap());
+        card.labels = vec![
+            String::from("pri:C"),
+            String::from("home repairs"),
+            String::from("@weekend"),
+        ];
+        let lists = [CardList::with_cards(String::from("Todo"), vec![card])];
+        let text = to_todotxt(&lists);
+        assert_eq!(text, "(C) 2024-03-01 Fix the sink +home_repairs @weekend\n");
+        assert_eq!(to_todotxt(&from_todotxt(&text)), text);
+    }
+
+    #[test]
+    fn keeps_tag_only_titles() {
+        let lists = from_todotxt("+inbox\n");
+        assert_eq!(lists[0][0].title, "+inbox");
+        assert_eq!(lists[0][0].labels, ["inbox"]);
+    }
+}
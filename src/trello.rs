@@ -0,0 +1,431 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, NaiveDate, SubsecRound, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::app::{Card, CardList, ChecklistItem};
+
+/// Card fields that are imported, or only hold Trello's own bookkeeping
+/// such as ids, positions, links and counters.
+const CARD_FIELDS: &[&str] = &[
+    "id",
+    "name",
+    "desc",
+    "descData",
+    "closed",
+    "idList",
+    "idBoard",
+    "idShort",
+    "pos",
+    "due",
+    "labels",
+    "idLabels",
+    "idMembers",
+    "idChecklists",
+    "dateLastActivity",
+    "shortLink",
+    "shortUrl",
+    "url",
+    "badges",
+    "subscribed",
+    "checkItemStates",
+    "nodeId",
+    "limits",
+];
+
+/// A board read from a Trello JSON export, with what it left behind.
+#[derive(Debug, Clone)]
+pub struct TrelloBoard {
+    pub name: String,
+    pub lists: Vec<CardList>,
+    /// Archived lists and cards, which are not imported.
+    pub archived_lists: usize,
+    pub archived_cards: usize,
+    /// Trello data without a place on the board, such as `"attachments on 3
+    /// cards"`.
+    pub unmapped: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Board {
+    name: String,
+    #[serde(default)]
+    desc: String,
+    #[serde(default)]
+    lists: Vec<List>,
+    #[serde(default)]
+    cards: Vec<Value>,
+    #[serde(default)]
+    checklists: Vec<Checklist>,
+    #[serde(default)]
+    members: Vec<Member>,
+    #[serde(default)]
+    actions: Vec<Action>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct List {
+    id: String,
+    name: String,
+    #[serde(default)]
+    closed: bool,
+    #[serde(default)]
+    pos: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloCard {
+    id: String,
+    name: String,
+    #[serde(default)]
+    desc: String,
+    #[serde(default)]
+    closed: bool,
+    id_list: String,
+    #[serde(default)]
+    pos: f64,
+    #[serde(default)]
+    due: Option<String>,
+    #[serde(default)]
+    labels: Vec<Label>,
+    #[serde(default)]
+    id_members: Vec<String>,
+    #[serde(default)]
+    date_last_activity: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Label {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    color: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Checklist {
+    id_card: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    pos: f64,
+    #[serde(default)]
+    check_items: Vec<CheckItem>,
+}
+
+#[derive(Deserialize)]
+struct CheckItem {
+    name: String,
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    pos: f64,
+}
+
+#[derive(Deserialize)]
+struct Member {
+    id: String,
+    #[serde(default)]
+    username: String,
+}
+
+#[derive(Deserialize)]
+struct Action {
+    #[serde(rename = "type", default)]
+    kind: String,
+}
+
+/// Reads a Trello board export, as downloaded from the board menu under
+/// "Print, export, and share".
+///
+/// Lists and cards keep their order. A card's labels are imported by name,
+/// or by color when unnamed, its first member becomes the assignee and its
+/// checklists are joined into one. Archived lists and cards are skipped.
+pub fn from_trello(text: &str) -> Result<TrelloBoard, String> {
+    let board: Board = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    let members: HashMap<&str, &str> = board
+        .members
+        .iter()
+        .map(|member| (member.id.as_str(), member.username.as_str()))
+        .collect();
+    let mut unmapped = Unmapped::default();
+    if !board.desc.is_empty() {
+        unmapped.board.push(String::from("the board description"));
+    }
+
+    let mut lists: Vec<&List> = board.lists.iter().filter(|list| !list.closed).collect();
+    lists.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+    let mut cards: Vec<Vec<(f64, Card)>> = vec![Vec::new(); lists.len()];
+    let mut card_ids = HashMap::new();
+    let mut archived_cards = 0;
+    for value in &board.cards {
+        let trello: TrelloCard =
+            serde_json::from_value(value.clone()).map_err(|e| e.to_string())?;
+        let Some(col) = lists.iter().position(|list| list.id == trello.id_list) else {
+            archived_cards += 1;
+            continue;
+        };
+        if trello.closed {
+            archived_cards += 1;
+            continue;
+        }
+        if let Some(fields) = value.as_object() {
+            let extra = fields.iter().filter(|(key, value)| match key.as_str() {
+                // Every card has one, only a color or image is worth reporting
+                "cover" => ["color", "idAttachment", "idUploadedBackground"]
+                    .iter()
+                    .any(|field| !value.get(field).is_none_or(is_empty)),
+                key => !CARD_FIELDS.contains(&key) && !is_empty(value),
+            });
+            for (key, _) in extra {
+                unmapped.add_to_card(key);
+            }
+        }
+        if trello.id_members.len() > 1 {
+            unmapped.add_to_card("members after the first");
+        }
+
+        let mut card = Card::new(trello.name);
+        card.description = trello.desc;
+        card.created_at = created_at(&trello.id).unwrap_or(card.created_at);
+        card.updated_at = trello
+            .date_last_activity
+            .as_deref()
+            .and_then(parse_time)
+            .unwrap_or(card.created_at);
+        card.due = trello.due.as_deref().and_then(parse_due);
+        card.labels = trello
+            .labels
+            .into_iter()
+            .filter_map(|label| match label.name.as_str() {
+                "" => label.color,
+                _ => Some(label.name),
+            })
+            .collect();
+        card.assignee = trello
+            .id_members
+            .first()
+            .map(|id| members.get(id.as_str()).copied().unwrap_or(id).to_string());
+        card_ids.insert(trello.id, (col, cards[col].len()));
+        cards[col].push((trello.pos, card));
+    }
+
+    let mut checklists: Vec<&Checklist> = board.checklists.iter().collect();
+    checklists.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+    let mut names: HashMap<&str, Vec<&str>> = HashMap::new();
+    for checklist in checklists {
+        let Some(&(col, row)) = card_ids.get(&checklist.id_card) else {
+            continue;
+        };
+        let card = &mut cards[col][row].1;
+        let mut items: Vec<&CheckItem> = checklist.check_items.iter().collect();
+        items.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+        card.checklist
+            .extend(items.into_iter().map(|item| ChecklistItem {
+                text: item.name.clone(),
+                done: item.state == "complete",
+            }));
+        names
+            .entry(&checklist.id_card)
+            .or_default()
+            .push(&checklist.name);
+    }
+    // A single checklist with the default name is all a card can hold
+    for names in names.values() {
+        if names[..] != ["Checklist"] {
+            unmapped.add_to_card("checklist names");
+        }
+    }
+    let comments = board
+        .actions
+        .iter()
+        .filter(|action| action.kind == "commentCard")
+        .count();
+    match comments {
+        0 => {}
+        1 => unmapped.board.push(String::from("1 comment")),
+        comments => unmapped.board.push(format!("{comments} comments")),
+    }
+
+    let lists = lists
+        .iter()
+        .zip(cards)
+        .map(|(list, mut cards)| {
+            cards.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let cards = cards.into_iter().map(|(_, card)| card).collect();
+            CardList::with_cards(list.name.clone(), cards)
+        })
+        .collect();
+    Ok(TrelloBoard {
+        name: board.name,
+        lists,
+        archived_lists: board.lists.iter().filter(|list| list.closed).count(),
+        archived_cards,
+        unmapped: unmapped.describe(),
+    })
+}
+
+/// Counts of Trello data that is not imported.
+#[derive(Default)]
+struct Unmapped {
+    board: Vec<String>,
+    cards: BTreeMap<String, usize>,
+}
+
+impl Unmapped {
+    fn add_to_card(&mut self, field: &str) {
+        *self.cards.entry(field.to_string()).or_default() += 1;
+    }
+
+    fn describe(self) -> Vec<String> {
+        let cards = self.cards.into_iter().map(|(field, count)| match count {
+            1 => format!("{field} on 1 card"),
+            count => format!("{field} on {count} cards"),
+        });
+        self.board.into_iter().chain(cards).collect()
+    }
+}
+
+/// Whether a field holds nothing worth reporting.
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Bool(value) => !value,
+        Value::String(value) => value.is_empty(),
+        Value::Array(values) => values.is_empty(),
+        Value::Object(fields) => fields.values().all(is_empty),
+        Value::Number(_) => false,
+    }
+}
+
+/// Trello ids start with the creation time, in seconds as hexadecimal.
+fn created_at(id: &str) -> Option<DateTime<Utc>> {
+    let seconds = i64::from_str_radix(id.get(..8)?, 16).ok()?;
+    DateTime::from_timestamp(seconds, 0)
+}
+
+fn parse_time(time: &str) -> Option<DateTime<Utc>> {
+    let time = DateTime::parse_from_rfc3339(time).ok()?;
+    Some(time.with_timezone(&Utc).trunc_subsecs(0))
+}
+
+/// The day a due time falls on in UTC, so that an import gives the same
+/// dates wherever it runs.
+fn parse_due(due: &str) -> Option<NaiveDate> {
+    parse_time(due).map(|time| time.date_naive())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A trimmed down Trello export with an archived list and card, an
+    /// unnamed label, two checklists on one card and a few things that are
+    /// not imported.
+    const EXPORT: &str = r#"{
+        "name": "Roadmap",
+        "desc": "Plans for the year",
+        "lists": [
+            {"id": "l2", "name": "Doing", "closed": false, "pos": 2},
+            {"id": "l1", "name": "Todo", "closed": false, "pos": 1},
+            {"id": "l3", "name": "Old", "closed": true, "pos": 3}
+        ],
+        "cards": [
+            {
+                "id": "5f000000aaaaaaaaaaaaaaaa", "name": "Second", "idList": "l1", "pos": 20,
+                "desc": "More to do", "due": "2024-05-10T23:30:00.000Z",
+                "labels": [{"name": "", "color": "red"}, {"name": "bug", "color": "blue"}],
+                "idMembers": ["m1", "m2"],
+                "dateLastActivity": "2024-05-02T16:00:00.000Z",
+                "cover": {"color": null, "idAttachment": null},
+                "attachments": [{"name": "log.txt"}]
+            },
+            {"id": "5f000001aaaaaaaaaaaaaaaa", "name": "First", "idList": "l1", "pos": 10,
+             "cover": {"color": null}},
+            {"id": "5f000002aaaaaaaaaaaaaaaa", "name": "Busy", "idList": "l2", "pos": 1},
+            {"id": "5f000003aaaaaaaaaaaaaaaa", "name": "Archived", "idList": "l2", "pos": 2,
+             "closed": true},
+            {"id": "5f000004aaaaaaaaaaaaaaaa", "name": "On old list", "idList": "l3", "pos": 1}
+        ],
+        "checklists": [
+            {"idCard": "5f000000aaaaaaaaaaaaaaaa", "name": "Later", "pos": 2,
+             "checkItems": [{"name": "c", "state": "incomplete", "pos": 1}]},
+            {"idCard": "5f000000aaaaaaaaaaaaaaaa", "name": "Checklist", "pos": 1,
+             "checkItems": [
+                {"name": "b", "state": "incomplete", "pos": 2},
+                {"name": "a", "state": "complete", "pos": 1}
+             ]},
+            {"idCard": "5f000002aaaaaaaaaaaaaaaa", "name": "Checklist", "pos": 1,
+             "checkItems": [{"name": "d", "state": "complete", "pos": 1}]}
+        ],
+        "members": [{"id": "m1", "username": "sam"}, {"id": "m2", "username": "kim"}],
+        "actions": [{"type": "commentCard"}, {"type": "updateCard"}]
+    }"#;
+
+    fn item(text: &str, done: bool) -> ChecklistItem {
+        ChecklistItem {
+            text: text.to_string(),
+            done,
+        }
+    }
+
+    #[test]
+    fn imports_open_lists_and_cards_in_order() {
+        let board = from_trello(EXPORT).unwrap();
+        assert_eq!(board.name, "Roadmap");
+        let names: Vec<&str> = board.lists.iter().map(CardList::name).collect();
+        assert_eq!(names, ["Todo", "Doing"]);
+        let titles: Vec<&str> = board.lists[0]
+            .cards()
+            .iter()
+            .map(|card| card.title.as_str())
+            .collect();
+        assert_eq!(titles, ["First", "Second"]);
+        assert_eq!(board.lists[1].len(), 1);
+        assert_eq!(board.archived_lists, 1);
+        assert_eq!(board.archived_cards, 2);
+    }
+
+    #[test]
+    fn maps_card_fields() {
+        let board = from_trello(EXPORT).unwrap();
+        let card = &board.lists[0][1];
+        assert_eq!(card.description, "More to do");
+        assert_eq!(card.labels, ["red", "bug"]);
+        assert_eq!(card.assignee.as_deref(), Some("sam"));
+        // The UTC day, whatever the local time zone
+        assert_eq!(card.due, NaiveDate::from_ymd_opt(2024, 5, 10));
+        assert_eq!(card.created_at.timestamp(), 0x5f000000);
+        assert_eq!(card.updated_at.to_rfc3339(), "2024-05-02T16:00:00+00:00");
+    }
+
+    #[test]
+    fn merges_checklists_in_order() {
+        let board = from_trello(EXPORT).unwrap();
+        assert_eq!(
+            board.lists[0][1].checklist,
+            [item("a", true), item("b", false), item("c", false)]
+        );
+        assert_eq!(board.lists[1][0].checklist, [item("d", true)]);
+    }
+
+    #[test]
+    fn reports_what_is_not_imported() {
+        let board = from_trello(EXPORT).unwrap();
+        assert_eq!(
+            board.unmapped,
+            [
+                "the board description",
+                "1 comment",
+                "attachments on 1 card",
+                "checklist names on 1 card",
+                "members after the first on 1 card",
+            ]
+        );
+    }
+}
@@ -1,116 +1,781 @@
 use ratatui::{
-    layout::{Alignment, Constraint, Layout},
-    style::{Color, Stylize},
-    widgets::{block::Title, Block, List, ListItem, Paragraph},
+    layout::{Alignment, Constraint, Flex, Layout, Margin, Position, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{
+        block::{self, Title},
+        Block, Clear, List, ListItem, ListState, Paragraph, Wrap,
+    },
     Frame,
 };
+use chrono::{DateTime, Local, Utc};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-use crate::{app::{App, AppMode}, db::read_board_name, db::Board};
+use crate::app::{App, AppMode, Card};
+use crate::keymap::{Action, KeymapMode};
 
+/// Draws the whole interface for `app`.
+///
+/// Rendering only reads from `App`; anything shown here has to be loaded into
+/// the app state beforehand, so drawing never touches the database and works
+/// the same on any ratatui backend.
 pub fn render(app: &App, frame: &mut Frame) {
-    let rects = Layout::vertical([
+    let [header, board, footer] = main_layout(frame.size());
+
+    render_header(app, frame, header);
+    render_lists(app, frame, board);
+    render_footer(app, frame, footer);
+
+    if matches!(app.mode(), AppMode::BoardPicker | AppMode::BoardEdit) {
+        render_board_picker(app, frame);
+    }
+    if matches!(
+        app.mode(),
+        AppMode::CardDetail | AppMode::ChecklistEdit | AppMode::DescriptionEdit
+    ) {
+        render_card_detail(app, frame);
+    }
+    if app.mode() == AppMode::Palette {
+        render_palette(app, frame);
+    }
+    if app.help() {
+        render_help(app, frame);
+    }
+}
+
+/// Splits the screen into header, board and footer.
+fn main_layout(area: Rect) -> [Rect; 3] {
+    Layout::vertical([
         Constraint::Length(3),
         Constraint::Fill(1),
         Constraint::Length(3),
     ])
-    .split(frame.size());
+    .areas(area)
+}
 
+/// The rectangles of the on-screen lists, in the order of
+/// [`App::visible_lists`].
+fn list_rects(app: &App, board: Rect) -> Vec<Rect> {
+    // Fewer lists than fit share the whole width
+    let cols = app.visible_lists().len();
+    Layout::horizontal(vec![Constraint::Ratio(1, cols as u32); cols])
+        .margin(1)
+        .spacing(1)
+        .split(board)
+        .to_vec()
+}
 
-    let board_name = match read_board_name("Default") {
+/// Finds what is drawn at a screen position: the index of the list, and of
+/// the card if the position is on one.
+pub fn hit_test(app: &App, column: u16, row: u16) -> Option<(usize, Option<usize>)> {
+    let [_, board, _] = main_layout(app.area());
+    let position = Position::new(column, row);
+    let (rect, list_index) = list_rects(app, board)
+        .into_iter()
+        .zip(app.visible_lists())
+        .find(|(rect, _)| rect.contains(position))?;
+    let inner = rect.inner(&Margin::new(1, 1));
+    let card_index = inner
+        .contains(position)
+        .then(|| app.card_offset(list_index) + usize::from(row - inner.y))
+        .and_then(|shown_index| app.shown_cards(list_index).get(shown_index).copied());
+    Some((list_index, card_index))
+}
 
-        Ok(user_data) => user_data,
+/// Draws the app title, the board name and the database path side by side.
+/// The path gives way to the board name, losing its leading directories
+/// first.
+fn render_header(app: &App, frame: &mut Frame, area: Rect) {
+    let block = Block::bordered();
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
 
-        Err(e) => {
+    let title = "Kanban 🦀 TUI";
+    let name = format!("📋️ {}", app.board().name);
+    let path = app.db_path().display().to_string();
+    let room = usize::from(inner.width).saturating_sub(title.width() + name.width() + 2);
+    let path = elide_start(&path, room);
+    let [title_area, name_area, path_area] = Layout::horizontal([
+        Constraint::Length(title.width() as u16),
+        Constraint::Fill(1),
+        Constraint::Length(path.width() as u16),
+    ])
+    .spacing(1)
+    .areas(inner);
 
-            eprintln!("Error reading user data: {}", e);
+    frame.render_widget(Paragraph::new(title), title_area);
+    frame.render_widget(Paragraph::new(name).centered(), name_area);
+    frame.render_widget(Paragraph::new(path), path_area);
+}
 
-            // Try to read the user data again or return a default user
+/// Shortens `path` to at most `width` columns by replacing its start with
+/// `…`, cutting at a directory separator where there is one.
+fn elide_start(path: &str, width: usize) -> String {
+    if path.width() <= width {
+        return path.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    // One column goes to the ellipsis
+    let mut start = path.len();
+    let mut used = 1;
+    for (index, c) in path.char_indices().rev() {
+        used += c.width().unwrap_or(0);
+        if used > width {
+            break;
+        }
+        start = index;
+    }
+    let tail = &path[start..];
+    match tail.find('/') {
+        Some(slash) if slash > 0 => format!("…{}", &tail[slash..]),
+        _ => format!("…{tail}"),
+    }
+}
 
-            match read_board_name("Jon") {
+fn render_lists(app: &App, frame: &mut Frame, area: Rect) {
+    let theme = app.theme();
+    let visible = app.visible_lists();
+    let list_rects = list_rects(app, area);
 
-                Ok(user_data) => user_data,
+    // Scroll indicators for lists off screen
+    let indicators = area.inner(&Margin::new(1, 0));
+    if visible.start > 0 {
+        frame.render_widget(
+            Paragraph::new(format!("◀ {}", visible.start)).left_aligned(),
+            indicators,
+        );
+    }
+    if visible.end < app.cols() {
+        frame.render_widget(
+            Paragraph::new(format!("{} ▶", app.cols() - visible.end)).right_aligned(),
+            indicators,
+        );
+    }
 
-                Err(e) => {
+    app.lists()[visible.clone()]
+        .iter()
+        .zip(visible.clone())
+        .for_each(|(list, list_index)| {
+            let rect = list_rects[list_index - visible.start];
+            let list_title = if app.col() == list_index && app.mode() == AppMode::ListEdit {
+                // Leave room for the corners and the card count
+                let (text, cursor) = app.editor().view(rect.width.saturating_sub(6));
+                frame.set_cursor(rect.x + 1 + cursor, rect.y);
+                format!("{text} ")
+            } else {
+                format!("{} ", list.name())
+            };
+            let shown = app.shown_cards(list_index);
+            let offset = app.card_offset(list_index);
+            let selected = shown.iter().position(|&row| row == app.row());
+            let mut state = ListState::default()
+                .with_offset(offset)
+                .with_selected(selected.filter(|_| list_index == app.col()));
+            let count = if app.search().is_active() {
+                format!("{}/{}", shown.len(), list.len())
+            } else {
+                list.len().to_string()
+            };
+            let mut block = Block::bordered()
+                .title(Title::from(list_title).alignment(Alignment::Left))
+                .title(Title::from(count).alignment(Alignment::Right))
+                .style(if list_index == app.col() {
+                    theme.focused
+                } else {
+                    theme.text
+                });
+            // Cards scrolled out of view
+            let below = shown.len().saturating_sub(offset + app.visible_rows());
+            if offset > 0 {
+                block = block.title(
+                    Title::from(format!("↑{offset}"))
+                        .position(block::Position::Bottom)
+                        .alignment(Alignment::Left),
+                );
+            }
+            if below > 0 {
+                block = block.title(
+                    Title::from(format!("↓{below}"))
+                        .position(block::Position::Bottom)
+                        .alignment(Alignment::Right),
+                );
+            }
+            // Inside the borders, after the highlight symbol
+            let card_edit = app.editor().view(rect.width.saturating_sub(4));
+            if list_index == app.col() && app.mode() == AppMode::CardEdit {
+                // Among the shown cards, as the search may hide some above
+                let y = rect.y + 1 + selected.unwrap_or(0).saturating_sub(offset) as u16;
+                frame.set_cursor(rect.x + 3 + card_edit.1, y);
+            }
+            frame.render_stateful_widget(
+                List::new(shown.iter().map(|&card_index| {
+                    let card = &list[card_index];
+                    let is_selected = card_index == app.row() && list_index == app.col();
+                    let line = if is_selected && app.mode() == AppMode::CardEdit {
+                        Line::raw(card_edit.0.clone())
+                    } else {
+                        card_line(card, app)
+                    };
+                    ListItem::new(line).style(if is_selected {
+                        theme.selected
+                    } else {
+                        theme.text
+                    })
+                }))
+                .block(block)
+                .highlight_symbol(">>")
+                .repeat_highlight_symbol(true),
+                rect,
+                &mut state,
+            );
+        });
+}
 
-                    eprintln!("Failed to read user data again: {}", e);
+/// The title of a card followed by its labels and due date, if any, with
+/// search matches highlighted.
+fn card_line(card: &Card, app: &App) -> Line<'static> {
+    let theme = app.theme();
+    let mut spans = highlighted(&card.title, Style::new(), app);
+    for label in &card.labels {
+        spans.push(Span::styled(" #", theme.muted));
+        spans.extend(highlighted(label, theme.muted, app));
+    }
+    if let Some(due) = card.due {
+        let due = format!(" ⏰{}", due.format("%b %d"));
+        spans.push(Span::styled(due, theme.muted));
+    }
+    if let Some((done, total)) = card.checklist_progress() {
+        spans.push(Span::styled(format!(" ☑ {done}/{total}"), theme.muted));
+    }
+    Line::from(spans)
+}
 
-                    Board { id: 0, name: String::new() }
+/// `text` split into spans so that the search matches in it stand out.
+fn highlighted(text: &str, style: Style, app: &App) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut start = 0;
+    for found in app.search().find(text) {
+        spans.push(Span::styled(text[start..found.start].to_string(), style));
+        let highlight = style.patch(app.theme().highlight);
+        spans.push(Span::styled(text[found.clone()].to_string(), highlight));
+        start = found.end;
+    }
+    spans.push(Span::styled(text[start..].to_string(), style));
+    spans.retain(|span| !span.content.is_empty());
+    spans
+}
 
-                }
-            }
+fn mode_name(mode: AppMode) -> &'static str {
+    match mode {
+        AppMode::Main => "Main",
+        AppMode::CardEdit => "CardEdit",
+        AppMode::ListEdit => "ListEdit",
+        AppMode::BoardPicker => "Boards",
+        AppMode::BoardEdit => "BoardEdit",
+        AppMode::CardDetail => "CardDetail",
+        AppMode::ChecklistEdit => "ChecklistEdit",
+        AppMode::DescriptionEdit => "DescriptionEdit",
+        AppMode::Search => "Search",
+        AppMode::Palette => "GoToCard",
+        AppMode::Command => "Command",
+    }
+}
+
+/// The actions worth a reminder in the footer, most useful first.
+fn footer_actions(app: &App) -> &'static [Action] {
+    match app.keymap_mode() {
+        KeymapMode::Main => &[
+            Action::AppendCard,
+            Action::EditCard,
+            Action::RemoveCard,
+            Action::OpenCard,
+            Action::MoveCardRight,
+            Action::AppendList,
+            Action::Search,
+            Action::OpenPalette,
+            Action::Undo,
+            Action::OpenBoards,
+            Action::Help,
+            Action::Quit,
+        ],
+        KeymapMode::BoardPicker => &[
+            Action::SwitchBoard,
+            Action::AppendBoard,
+            Action::EditBoard,
+            Action::RemoveBoard,
+            Action::Help,
+            Action::Close,
+        ],
+        KeymapMode::CardDetail => &[
+            Action::ToggleItem,
+            Action::AppendItem,
+            Action::EditItem,
+            Action::RemoveItem,
+            Action::MoveItemDown,
+            Action::EditDescription,
+            Action::Help,
+            Action::Close,
+        ],
+        KeymapMode::Edit if app.mode() == AppMode::Search => {
+            &[Action::Confirm, Action::Cancel, Action::ToggleRegex]
+        }
+        KeymapMode::Edit if app.mode() == AppMode::Command => {
+            &[Action::Confirm, Action::Cancel, Action::Complete]
+        }
+        KeymapMode::Edit if app.mode() == AppMode::DescriptionEdit => {
+            &[Action::Confirm, Action::Cancel, Action::NewLine]
+        }
+        KeymapMode::Edit => &[Action::Confirm, Action::Cancel, Action::DeleteWord],
+        KeymapMode::Help => &[Action::MotionDown, Action::MotionUp, Action::Close],
+        KeymapMode::Palette => &[
+            Action::Confirm,
+            Action::Cancel,
+            Action::MotionDown,
+            Action::MotionUp,
+        ],
+    }
+}
+
+/// The bindings of the current keymap, each action with its first chord, as
+/// many as fit in `width`.
+fn key_hints(app: &App, width: u16) -> Line<'static> {
+    let mode = app.keymap_mode();
+    let width = usize::from(width);
+    let mut spans = Vec::new();
+    let mut used = 0;
+    for &action in footer_actions(app) {
+        let Some(chord) = app.keymap().chords(mode, action).next() else {
+            continue;
+        };
+        let chord = chord.to_string();
+        let hint = format!(" {}", action.description());
+        let gap = if spans.is_empty() { 0 } else { 2 };
+        let hint_width = gap + chord.width() + hint.width();
+        if used + hint_width > width {
+            break;
         }
+        used += hint_width;
+        if gap > 0 {
+            spans.push(Span::raw("  "));
+        }
+        spans.push(chord.bold());
+        spans.push(Span::raw(hint));
+    }
+    Line::from(spans)
+}
+
+/// The number of cards matching the search, or why it is invalid.
+fn search_status(app: &App) -> String {
+    match app.search().error() {
+        Some(error) => error.to_string(),
+        None => format!("{} matches", app.matching_cards().len()),
+    }
+}
+
+fn render_footer(app: &App, frame: &mut Frame, area: Rect) {
+    match app.mode() {
+        AppMode::Search => {
+            let title = if app.search().regex() {
+                "Search (regex)"
+            } else {
+                "Search"
+            };
+            let status = (!app.search().query().is_empty()).then(|| search_status(app));
+            render_prompt(app, frame, area, '/', title, status);
+            return;
+        }
+        AppMode::Command => {
+            let status = app.message().map(String::from);
+            render_prompt(app, frame, area, ':', "Command", status);
+            return;
+        }
+        _ => {}
+    }
+    let title = if app.help() {
+        "Help"
+    } else {
+        mode_name(app.mode())
     };
+    let mut block = Block::bordered().title(Title::from(title).alignment(Alignment::Left));
+    if app.search().is_active() {
+        let filter = format!(" /{}  {} ", app.search().query(), search_status(app));
+        block = block.title(Title::from(filter).alignment(Alignment::Right));
+    }
+    // The outcome of a command stands in for the key hints until the next key
+    let content = match app.message() {
+        Some(message) => Line::raw(message.to_string()),
+        None => key_hints(app, area.width.saturating_sub(2)),
+    };
+    frame.render_widget(Paragraph::new(content).centered().block(block), area);
+}
 
-    // Header
-    frame.render_widget(
-        Paragraph::new("Kanban 🦀 TUI")
-            .left_aligned()
-            .block(Block::bordered()),
-        rects[0],
+/// The text being typed after `prefix`, in place of the footer, with
+/// `status` on the right.
+fn render_prompt(
+    app: &App,
+    frame: &mut Frame,
+    area: Rect,
+    prefix: char,
+    title: &str,
+    status: Option<String>,
+) {
+    let mut block = Block::bordered()
+        .title(Title::from(title).alignment(Alignment::Left))
+        .title(
+            Title::from(key_hints(app, area.width.saturating_sub(4)))
+                .position(block::Position::Bottom)
+                .alignment(Alignment::Right),
+        );
+    if let Some(status) = status {
+        block = block.title(Title::from(format!(" {status} ")).alignment(Alignment::Right));
+    }
+    // Inside the border, after the prefix
+    let (text, cursor) = app.editor().view(area.width.saturating_sub(4));
+    frame.set_cursor(area.x + 2 + cursor, area.y + 1);
+    frame.render_widget(Paragraph::new(format!("{prefix}{text}")).block(block), area);
+}
+
+fn render_board_picker(app: &App, frame: &mut Frame) {
+    let theme = app.theme();
+    let rows = app.boards().len().min(app.visible_boards());
+    let area = centered_rect(40, rows as u16 + 2, frame.size());
+    // Inside the borders, leaving room for the current board marker
+    let board_edit = app.editor().view(area.width.saturating_sub(4));
+    if app.mode() == AppMode::BoardEdit {
+        let y = area.y + 1 + (app.board_row() - app.board_offset()) as u16;
+        frame.set_cursor(area.x + 1 + board_edit.1, y);
+    }
+    let mut state = ListState::default()
+        .with_offset(app.board_offset())
+        .with_selected(Some(app.board_row()));
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(
+        List::new(app.boards().iter().enumerate().map(|(index, board)| {
+            let is_selected = index == app.board_row();
+            let mut text = if is_selected && app.mode() == AppMode::BoardEdit {
+                board_edit.0.clone()
+            } else {
+                board.name.clone()
+            };
+            if board.id == app.board().id {
+                text.push_str(" *");
+            }
+            ListItem::new(text).style(if is_selected {
+                theme.selected
+            } else {
+                theme.text
+            })
+        }))
+        .block(
+            Block::bordered()
+                .title(Title::from("Boards").alignment(Alignment::Left))
+                .style(theme.focused),
+        ),
+        area,
+        &mut state,
     );
+}
 
-    let name = format!("📋️ {}", board_name.name.to_string());
+fn render_card_detail(app: &App, frame: &mut Frame) {
+    let Some(card) = app.card() else {
+        return;
+    };
+    let theme = app.theme();
+    let size = frame.size();
+    let area = centered_rect(
+        size.width.saturating_sub(4).min(72),
+        size.height.saturating_sub(4).min(24),
+        size,
+    );
+    let block = Block::bordered()
+        .title(Title::from(format!("{} ", card.title)).alignment(Alignment::Left))
+        .style(theme.focused);
+    let inner = block.inner(area);
+    let field = |name: &'static str, value: String| {
+        Line::from(vec![format!("{name:<10}").bold(), Span::raw(value)])
+    };
+    let date_time = |time: DateTime<Utc>| {
+        time.with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    };
 
+    let mut lines = vec![field("List", app.list().name().to_string())];
+    if !card.labels.is_empty() {
+        let labels = card.labels.iter().map(|label| format!("#{label}"));
+        lines.push(field("Labels", labels.collect::<Vec<_>>().join(" ")));
+    }
+    if let Some(assignee) = &card.assignee {
+        lines.push(field("Assignee", assignee.clone()));
+    }
+    if let Some(due) = card.due {
+        lines.push(field("Due", due.format("%Y-%m-%d").to_string()));
+    }
+    lines.push(field("Created", date_time(card.created_at)));
+    lines.push(field("Updated", date_time(card.updated_at)));
+    if let Some((done, total)) = card.checklist_progress() {
+        lines.push(Line::default());
+        lines.push(field("Checklist", format!("{done}/{total}")));
+        // After the "[ ] " box
+        let check_edit = app.editor().view(inner.width.saturating_sub(4));
+        let first_item = lines.len();
+        if app.mode() == AppMode::ChecklistEdit {
+            let line = (first_item + app.check_row()) as u16;
+            if let Some(y) = line.checked_sub(app.detail_scroll()) {
+                if y < inner.height {
+                    frame.set_cursor(inner.x + 4 + check_edit.1, inner.y + y);
+                }
+            }
+        }
+        lines.extend(card.checklist.iter().enumerate().map(|(index, item)| {
+            let is_selected = index == app.check_row();
+            let text = if is_selected && app.mode() == AppMode::ChecklistEdit {
+                check_edit.0.clone()
+            } else {
+                item.text.clone()
+            };
+            let text = format!("[{}] {}", if item.done { 'x' } else { ' ' }, text);
+            Line::from(text).style(if is_selected {
+                theme.selected
+            } else {
+                theme.text
+            })
+        }));
+    }
+    lines.push(Line::default());
+    if app.mode() == AppMode::DescriptionEdit {
+        // Line breaks show as ↵ while editing
+        let (text, cursor) = app.editor().view(inner.width);
+        if let Some(y) = (lines.len() as u16).checked_sub(app.detail_scroll()) {
+            if y < inner.height {
+                frame.set_cursor(inner.x + cursor, inner.y + y);
+            }
+        }
+        lines.push(Line::raw(text));
+    } else if card.description.is_empty() {
+        lines.push(Line::styled("No description", theme.muted));
+    } else {
+        lines.extend(card.description.lines().map(|line| Line::raw(line.to_string())));
+    }
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
     frame.render_widget(
-        Paragraph::new(name)
-            .centered()
-            .block(Block::bordered()),
-        rects[0],
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((app.detail_scroll(), 0))
+            .style(theme.text),
+        inner,
     );
+}
 
-    // Lists and cards
-    // TODO: Slice lists
-    let list_rects = Layout::horizontal(vec![Constraint::Percentage(20); app.lists().len()])
-        .margin(1)
-        .spacing(1)
-        .split(rects[1]);
-    app.lists()
-        .iter()
-        .enumerate()
-        .for_each(|(list_index, list)| {
-            let list_title = if app.col() == list_index && app.mode() == AppMode::ListEdit {
-                format!("{}_ ", list.name())
+/// The go to card popup: the query, and the matching cards with their lists.
+fn render_palette(app: &App, frame: &mut Frame) {
+    let theme = app.theme();
+    let size = frame.size();
+    let area = centered_rect(
+        size.width.saturating_sub(4).min(60),
+        size.height.saturating_sub(4).min(20),
+        size,
+    );
+    let block = Block::bordered()
+        .title(Title::from("Go to card").alignment(Alignment::Left))
+        .style(theme.focused);
+    let inner = block.inner(area);
+    let [prompt, results] =
+        Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(inner);
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    // After the "> " prompt
+    let (text, cursor) = app.editor().view(prompt.width.saturating_sub(2));
+    frame.set_cursor(prompt.x + 2 + cursor, prompt.y);
+    frame.render_widget(Paragraph::new(format!("> {text}")).style(theme.text), prompt);
+
+    if app.palette_items().is_empty() {
+        frame.render_widget(
+            Paragraph::new("No matching cards").style(theme.muted),
+            results,
+        );
+        return;
+    }
+    let items = app.palette_items().iter().enumerate().map(|(index, item)| {
+        let list = &app.lists()[item.col];
+        let mut spans = Vec::new();
+        for (char_index, c) in list[item.row].title.chars().enumerate() {
+            let style = if item.positions.contains(&char_index) {
+                theme.highlight
             } else {
-                format!("{} ", list.name())
+                Style::new()
             };
-            frame.render_widget(
-                List::new(list.cards().iter().enumerate().map(|(card_index, card)| {
-                    let is_selected = card_index == app.row() && list_index == app.col();
-                    let mut text = card.clone();
-                    if is_selected && app.mode() == AppMode::CardEdit {
-                        text.push('_');
-                    }
-                    ListItem::new(text).fg(if is_selected {
-                        Color::Green
-                    } else {
-                        Color::White
-                    })
-                })).highlight_symbol(">>")
-                .block(
-                    Block::bordered()
-                        .title(Title::from(list_title).alignment(Alignment::Left))
-                        .title(Title::from(list.len().to_string()).alignment(Alignment::Right))
-                        .fg(if list_index == app.col() {
-                            Color::LightBlue
-                        } else {
-                            Color::White
-                        }),
-                ).highlight_symbol(">>")
-                .repeat_highlight_symbol(true),
-                list_rects[list_index],
-            );
-        });
+            spans.push(Span::styled(c.to_string(), style));
+        }
+        spans.push(Span::styled(format!("  {}", list.name()), theme.muted));
+        ListItem::new(Line::from(spans)).style(if index == app.palette_row() {
+            theme.selected
+        } else {
+            theme.text
+        })
+    });
+    let mut state = ListState::default().with_selected(Some(app.palette_row()));
+    frame.render_stateful_widget(List::new(items), results, &mut state);
+}
 
-    // Footer
-    // TODO: Keybindings for each mode
+/// Every binding of the keymap behind the overlay, grouped by category.
+fn render_help(app: &App, frame: &mut Frame) {
+    let theme = app.theme();
+    let mode = app.mode().into();
+    let keymap = app.keymap();
+
+    // Each action once, with all of its chords, in keymap order
+    let mut actions: Vec<Action> = Vec::new();
+    for &(_, action) in keymap.bindings(mode) {
+        if !actions.contains(&action) {
+            actions.push(action);
+        }
+    }
+    let mut categories: Vec<&str> = Vec::new();
+    for action in &actions {
+        if !categories.contains(&action.category()) {
+            categories.push(action.category());
+        }
+    }
+    let mut lines = Vec::new();
+    for category in categories {
+        if !lines.is_empty() {
+            lines.push(Line::default());
+        }
+        lines.push(Line::from(category.bold()));
+        for &action in actions.iter().filter(|a| a.category() == category) {
+            let chords: Vec<_> = keymap.chords(mode, action).map(|c| c.to_string()).collect();
+            lines.push(Line::from(vec![
+                Span::raw(format!("  {:<20}", chords.join(", "))),
+                Span::raw(action.description()),
+            ]));
+        }
+    }
+
+    let close: Vec<String> = keymap
+        .chords(KeymapMode::Help, Action::Close)
+        .map(|chord| chord.to_string())
+        .collect();
+    let size = frame.size();
+    let area = centered_rect(
+        size.width.saturating_sub(4).min(48),
+        size.height.saturating_sub(4).min(lines.len() as u16 + 2),
+        size,
+    );
+    let block = Block::bordered()
+        .title(Title::from(format!("Help: {} ", mode_name(app.mode()))).alignment(Alignment::Left))
+        .title(
+            Title::from(format!(" {} to close ", close.join("/")))
+                .position(block::Position::Bottom)
+                .alignment(Alignment::Right),
+        )
+        .style(theme.focused);
+    let inner = block.inner(area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
     frame.render_widget(
-        Paragraph::new(match app.mode() {
-            AppMode::Main => "Main",
-            AppMode::CardEdit => "CardEdit",
-            AppMode::ListEdit => "ListEdit",
-        })
-        .centered()
-        .block(Block::bordered()),
-        rects[2],
+        Paragraph::new(lines)
+            .scroll((app.help_scroll(), 0))
+            .style(theme.text),
+        inner,
     );
 }
+
+/// A `width` x `height` rectangle centered in `area`, clamped to fit.
+fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let [area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use ratatui::{backend::TestBackend, Terminal};
+
+    use super::*;
+
+    /// The text of each row of the rendered screen.
+    fn draw(app: &App) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        terminal.draw(|frame| render(app, frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer.get(x, y).symbol())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn renders_header_lists_and_footer() {
+        let rows = draw(&App::new());
+        assert!(rows[1].contains("Kanban"));
+        assert!(rows[1].contains("Default"));
+        assert!(rows[4].starts_with(" ┌List 1 "));
+        // A single list takes the whole width
+        assert!(rows[4].ends_with("2┐ "));
+        assert!(rows[5].contains(">>Card 1 in List 1"));
+        assert!(rows[6].contains("  Card 2 in List 1"));
+        assert!(rows[21].starts_with("┌Main"));
+        assert!(rows[22].contains("a append card"));
+    }
+
+    #[test]
+    fn header_keeps_the_board_name_next_to_the_path() {
+        let mut app = App::new();
+        let path = "/home/alice/.local/share/kanban-tui/board.db";
+        app.set_db_path(PathBuf::from(path));
+        let rows = draw(&app);
+        assert!(rows[1].starts_with("│Kanban"));
+        assert!(rows[1].contains("Default"));
+        assert!(rows[1].ends_with(&format!(" {path}│")));
+
+        // Paths too long for the header lose their first directories
+        let long = "/home/alice/projects/with/a/rather/deep/tree/kanban-tui/board.db";
+        app.set_db_path(PathBuf::from(long));
+        let rows = draw(&app);
+        assert!(rows[1].contains("Default"));
+        assert!(rows[1].ends_with(" …/with/a/rather/deep/tree/kanban-tui/board.db│"));
+    }
+
+    #[test]
+    fn edit_cursor_is_on_the_card_shown_by_the_search() {
+        let mut app = App::new();
+        app.paste_cards(&["Other", "Match"]);
+        app.start_search();
+        app.editor_mut().insert_str("match");
+        app.update_search();
+        app.done_search();
+        app.edit_card();
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        terminal.draw(|frame| render(&app, frame)).unwrap();
+        let rows = draw(&app);
+        assert!(rows[5].contains("Match"));
+        let (_, y) = terminal.get_cursor().unwrap();
+        assert_eq!(y, 5);
+    }
+
+    #[test]
+    fn paths_are_elided_at_a_separator() {
+        let path = "/home/alice/.local/share/kanban-tui/board.db";
+        assert_eq!(elide_start(path, 44), path);
+        assert_eq!(elide_start(path, 24), "…/kanban-tui/board.db");
+        assert_eq!(elide_start(path, 9), "…board.db");
+        assert_eq!(elide_start(path, 4), "….db");
+        assert_eq!(elide_start(path, 0), "");
+    }
+}
@@ -0,0 +1,176 @@
+use std::{env, fs, process};
+
+use kanban_tui::{
+    app::{App, AppMode, CardList},
+    cli::Args,
+    db,
+};
+
+/// Runs a subcommand and returns what it printed.
+fn run(args: &[&str]) -> Result<String, String> {
+    let args =
+        Args::parse_from(args.iter().map(|arg| arg.to_string())).map_err(|e| e.to_string())?;
+    let command = args.command.ok_or("no subcommand")?;
+    let mut out = Vec::new();
+    command.run(&mut out).map_err(|e| e.to_string())?;
+    String::from_utf8(out).map_err(|e| e.to_string())
+}
+
+fn titles(list: &CardList) -> Vec<&str> {
+    list.cards()
+        .iter()
+        .map(|card| card.title.as_str())
+        .collect()
+}
+
+fn board_lists(name: &str) -> Vec<CardList> {
+    let board = db::find_board(name).unwrap().unwrap();
+    db::read_lists(board.id).unwrap()
+}
+
+/// The database path can only be set once per process, so every scenario
+/// shares one file and keeps to its own board.
+#[test]
+fn scripts_share_the_database_with_the_app() {
+    let path = env::temp_dir().join(format!("kanban-tui-cli-{}.db", process::id()));
+    let _ = fs::remove_file(&path);
+    db::set_db_path(path.clone());
+
+    adds_and_moves_cards_on_a_fresh_database();
+    app_reloads_a_board_changed_by_a_script();
+    opens_the_board_opened_last();
+
+    let _ = fs::remove_file(&path);
+}
+
+fn adds_and_moves_cards_on_a_fresh_database() {
+    // The default board is created like the app creates it
+    assert_eq!(run(&["lists"]).unwrap(), "List 1\t2\n");
+    assert_eq!(run(&["add", "Fix", "bug"]).unwrap(), "");
+    let lists = board_lists(db::DEFAULT_BOARD);
+    assert_eq!(
+        titles(&lists[0]),
+        ["Card 1 in List 1", "Card 2 in List 1", "Fix bug"]
+    );
+
+    run(&["add", "--list=list 1", "--", "-v flag"]).unwrap();
+    let id = board_lists(db::DEFAULT_BOARD)[0][3].id.unwrap();
+    assert_eq!(
+        run(&["move", &id.to_string(), "Done"]),
+        Err(String::from(
+            "no list named 'Done' on board 'Default', it has List 1"
+        ))
+    );
+    assert_eq!(
+        run(&["ls", "--board", "Missing"]),
+        Err(String::from("no board named 'Missing'"))
+    );
+
+    // A board without lists gets one, as in the app
+    let empty = db::insert_board("Empty").unwrap();
+    run(&["add", "--board", "Empty", "First"]).unwrap();
+    let lists = board_lists("Empty");
+    assert_eq!(lists.len(), 1);
+    assert_eq!(titles(&lists[0]), ["New Card", "First"]);
+
+    db::write_lists(
+        empty.id,
+        &mut [
+            CardList::with_cards(String::from("Todo"), Vec::new()),
+            CardList::with_cards(String::from("Done"), Vec::new()),
+        ],
+    )
+    .unwrap();
+    run(&["add", "--board", "Empty", "Task"]).unwrap();
+    let id = board_lists("Empty")[0][0].id.unwrap();
+    run(&["move", &id.to_string(), "done"]).unwrap();
+    let lists = board_lists("Empty");
+    assert!(lists[0].is_empty());
+    assert_eq!(titles(&lists[1]), ["Task"]);
+    assert_eq!(lists[1][0].id, Some(id));
+    assert_eq!(
+        run(&["lists", "--board", "Empty"]).unwrap(),
+        "Todo\t0\nDone\t1\n"
+    );
+    assert_eq!(
+        run(&["ls", "--board", "Empty", "--list", "done"]).unwrap(),
+        format!("{id}\tDone\tTask\n")
+    );
+}
+
+fn app_reloads_a_board_changed_by_a_script() {
+    let mut app = App::load("Shared").unwrap();
+    run(&["add", "--board", "Shared", "From a script"]).unwrap();
+
+    // The app's next change would overwrite the script's, so it reloads
+    app.move_down();
+    app.save().unwrap();
+    assert_eq!(
+        titles(&app.lists()[0]),
+        ["Card 1 in List 1", "Card 2 in List 1", "From a script"]
+    );
+    assert!(app.message().is_some());
+    assert_eq!(board_lists("Shared"), app.lists());
+
+    // Saving on top of the reloaded board works again
+    app.select(0, Some(0));
+    app.move_down();
+    app.save().unwrap();
+    assert_eq!(
+        titles(&board_lists("Shared")[0]),
+        ["Card 2 in List 1", "Card 1 in List 1", "From a script"]
+    );
+
+    // The change that was not saved is gone from the undo history, so undo
+    // cannot write the board from before the script back
+    app.undo();
+    app.undo();
+    app.save().unwrap();
+    assert_eq!(
+        titles(&board_lists("Shared")[0]),
+        ["Card 1 in List 1", "Card 2 in List 1", "From a script"]
+    );
+
+    // An idle app picks up a script's change on the next tick
+    run(&["add", "--board", "Shared", "Picked up"]).unwrap();
+    assert!(app.tick().unwrap());
+    assert_eq!(board_lists("Shared"), app.lists());
+    assert!(!app.tick().unwrap());
+
+    // but leaves the board alone while it has unsaved changes
+    app.select(0, Some(0));
+    app.move_down();
+    run(&["add", "--board", "Shared", "Not yet"]).unwrap();
+    assert!(!app.tick().unwrap());
+    app.save().unwrap();
+    assert!(titles(&app.lists()[0]).contains(&"Not yet"));
+
+    // The card detail closes when another instance removes its card
+    let last = app.lists()[0].len() - 1;
+    app.select(0, Some(last));
+    app.open_card_detail();
+    let mut other = App::load("Shared").unwrap();
+    other.select(0, Some(last));
+    other.remove_card();
+    other.save().unwrap();
+    assert!(app.tick().unwrap());
+    assert_eq!(app.mode(), AppMode::Main);
+    assert!(app.card().is_some());
+}
+
+fn opens_the_board_opened_last() {
+    // The app scenario opened Shared last
+    let default = db::find_board(db::DEFAULT_BOARD).unwrap().unwrap();
+    db::rename_board(default.id, "Renamed").unwrap();
+    let app = App::load_last().unwrap();
+    assert_eq!(app.board().name, "Shared");
+
+    run(&["add", "Without a board"]).unwrap();
+    let lists = board_lists("Shared");
+    assert_eq!(titles(&lists[0]).last(), Some(&"Without a board"));
+    assert!(db::find_board(db::DEFAULT_BOARD).unwrap().is_none());
+
+    // Opening a board makes it the one opened next
+    App::load("Renamed").unwrap();
+    assert_eq!(App::load_last().unwrap().board().name, "Renamed");
+}